@@ -2495,10 +2495,12 @@ impl<'a, 'gc> Activation<'a, 'gc> {
                     .finish();
 
                 match method {
-                    NavigationMethod::Get if !url.contains(b'?') => {
-                        Request::get(format!("{url}?{qstring}"))
+                    NavigationMethod::Get | NavigationMethod::Head if !url.contains(b'?') => {
+                        Request::request(method, format!("{url}?{qstring}"), None)
+                    }
+                    NavigationMethod::Get | NavigationMethod::Head => {
+                        Request::request(method, format!("{url}&{qstring}"), None)
                     }
-                    NavigationMethod::Get => Request::get(format!("{url}&{qstring}")),
                     NavigationMethod::Post => Request::post(
                         url.to_utf8_lossy().into_owned(),
                         Some((
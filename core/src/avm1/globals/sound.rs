@@ -6,7 +6,7 @@ use crate::avm1::clamp::Clamp;
 use crate::avm1::error::Error;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Object, ScriptObject, SoundObject, TObject, Value};
-use crate::backend::navigator::Request;
+use crate::backend::navigator::{FuturePriority, Request};
 use crate::character::Character;
 use crate::context::GcContext;
 use crate::display_object::{SoundTransform, TDisplayObject};
@@ -259,7 +259,10 @@ fn load_sound<'gc>(
                 Request::get(url.to_utf8_lossy().into_owned()),
                 is_streaming,
             );
-            activation.context.navigator.spawn_future(future);
+            activation
+                .context
+                .navigator
+                .spawn_future_with_priority(future, FuturePriority::Low);
         }
     }
     Ok(Value::Undefined)
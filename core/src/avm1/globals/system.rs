@@ -41,6 +41,7 @@ impl fmt::Display for CpuArchitecture {
 
 /// Available type of sandbox for a given SWF
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SandboxType {
     Remote,
     LocalWithFile,
@@ -8,7 +8,7 @@ use crate::display_object::TDisplayObject;
 use crate::socket::SocketHandle;
 use crate::string::AvmString;
 use gc_arena::{Collect, Gc};
-use std::cell::{Cell, RefCell, RefMut};
+use std::cell::Cell;
 
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
@@ -16,7 +16,6 @@ struct XmlSocketData {
     handle: Cell<Option<SocketHandle>>,
     /// Connection timeout in milliseconds.
     timeout: Cell<u32>,
-    read_buffer: RefCell<Vec<u8>>,
 }
 
 #[derive(Clone, Debug, Collect)]
@@ -41,10 +40,6 @@ impl<'gc> XmlSocket<'gc> {
         self.0.timeout.set(new_timeout);
     }
 
-    pub fn read_buffer(&self) -> RefMut<'_, Vec<u8>> {
-        self.0.read_buffer.borrow_mut()
-    }
-
     pub fn cast(value: Value<'gc>) -> Option<Self> {
         if let Value::Object(object) = value {
             if let NativeObject::XmlSocket(xml_socket) = object.native() {
@@ -237,7 +232,6 @@ pub fn constructor<'gc>(
             handle: Cell::new(None),
             // Default timeout is 20_000 milliseconds (20 seconds)
             timeout: Cell::new(20000),
-            read_buffer: RefCell::new(Vec::new()),
         },
     ));
 
@@ -111,6 +111,7 @@ pub struct SystemClasses<'gc> {
     pub qname: ClassObject<'gc>,
     pub mouseevent: ClassObject<'gc>,
     pub progressevent: ClassObject<'gc>,
+    pub outputprogressevent: ClassObject<'gc>,
     pub httpstatusevent: ClassObject<'gc>,
     pub textevent: ClassObject<'gc>,
     pub errorevent: ClassObject<'gc>,
@@ -239,6 +240,7 @@ impl<'gc> SystemClasses<'gc> {
             qname: object,
             mouseevent: object,
             progressevent: object,
+            outputprogressevent: object,
             httpstatusevent: object,
             textevent: object,
             errorevent: object,
@@ -796,6 +798,7 @@ fn load_playerglobal<'gc>(
             ("flash.events", "ErrorEvent", errorevent),
             ("flash.events", "KeyboardEvent", keyboardevent),
             ("flash.events", "ProgressEvent", progressevent),
+            ("flash.events", "OutputProgressEvent", outputprogressevent),
             ("flash.events", "HTTPStatusEvent", httpstatusevent),
             ("flash.events", "SecurityErrorEvent", securityerrorevent),
             ("flash.events", "IOErrorEvent", ioerrorevent),
@@ -164,7 +164,7 @@ pub fn request_from_url_request<'gc>(
     let data = url_request.get_public_property("data", activation)?;
     let body = match (method, data) {
         (_, Value::Null | Value::Undefined) => None,
-        (NavigationMethod::Get, data) => {
+        (NavigationMethod::Get | NavigationMethod::Head, data) => {
             // This looks "wrong" but it's Flash-correct.
             // It simply appends the data to the URL if there's already a query,
             // otherwise it adds ?data.
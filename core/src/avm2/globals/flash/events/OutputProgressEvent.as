@@ -0,0 +1,25 @@
+package flash.events {
+    public class OutputProgressEvent extends Event {
+        public static const OUTPUT_PROGRESS:String = "outputProgress";
+
+        public var bytesPending:Number;
+        public var bytesTotal:Number;
+
+        public function OutputProgressEvent(type:String, bubbles:Boolean = false, cancelable:Boolean = false, bytesPending:Number = 0, bytesTotal:Number = 0)
+        {
+            super(type,bubbles,cancelable);
+            this.bytesPending = bytesPending;
+            this.bytesTotal = bytesTotal;
+        }
+
+        override public function clone() : Event
+        {
+            return new OutputProgressEvent(this.type,this.bubbles,this.cancelable,this.bytesPending,this.bytesTotal);
+        }
+
+        override public function toString() : String
+        {
+            return this.formatToString("OutputProgressEvent","type","bubbles","cancelable","eventPhase","bytesPending","bytesTotal");
+        }
+    }
+}
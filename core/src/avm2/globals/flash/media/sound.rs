@@ -6,7 +6,7 @@ use crate::avm2::parameters::ParametersExt;
 use crate::avm2::value::Value;
 use crate::avm2::Avm2;
 use crate::avm2::Error;
-use crate::backend::navigator::Request;
+use crate::backend::navigator::{FuturePriority, Request};
 use crate::character::Character;
 use crate::display_object::SoundTransform;
 use crate::string::AvmString;
@@ -258,7 +258,10 @@ pub fn load<'gc>(
         // FIXME: Set options from the `URLRequest`.
         Request::get(url.to_string()),
     );
-    activation.context.navigator.spawn_future(future);
+    activation
+        .context
+        .navigator
+        .spawn_future_with_priority(future, FuturePriority::Low);
 
     Ok(Value::Undefined)
 }
@@ -5,8 +5,6 @@ package flash.net {
     import flash.utils.IDataInput;
     import flash.utils.IDataOutput;
 
-    import __ruffle__.stub_getter;
-
     [Ruffle(InstanceAllocator)]
     public class Socket extends EventDispatcher implements IDataOutput, IDataInput {
 
@@ -26,10 +24,7 @@ package flash.net {
 
         public native function get bytesAvailable():uint;
 
-        public function get bytesPending():uint {
-            stub_getter("flash.net.Socket", "bytesPending");
-            return 0;
-        }
+        public native function get bytesPending():uint;
 
         public native function get endian():String;
         public native function set endian(value:String):void;
@@ -1,7 +1,6 @@
 package flash.net {
 	import flash.events.EventDispatcher;
 	import flash.net.URLRequest;
-	import __ruffle__.stub_method;
 
 	public class URLLoader extends EventDispatcher {
 		public var data: *;
@@ -28,8 +27,6 @@ package flash.net {
 		}
 		public native function load(request:URLRequest):void;
 
-		public function close():void {
-			stub_method("flash.net.URLLoader", "close");
-		}
+		public native function close():void;
 	}
 }
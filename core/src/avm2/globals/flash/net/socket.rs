@@ -108,6 +108,23 @@ pub fn get_bytes_available<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn get_bytes_pending<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(socket) = this.as_socket() {
+        // Flushed bytes are pending until they're written to the connection.
+        let flushed = socket
+            .handle()
+            .map(|handle| activation.context.sockets.bytes_pending(handle))
+            .unwrap_or_default();
+        return Ok((socket.write_buffer().len() + flushed).into());
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn get_endian<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -36,6 +36,16 @@ pub fn load<'gc>(
     spawn_fetch(activation, this, request, data_format)
 }
 
+/// Native function definition for `URLLoader.close`
+pub fn close<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    activation.context.load_manager.close_url_loader(this);
+    Ok(Value::Undefined)
+}
+
 fn spawn_fetch<'gc>(
     activation: &mut Activation<'_, 'gc>,
     loader_object: Object<'gc>,
@@ -13,6 +13,8 @@ use std::future::Future;
 use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Poll, Waker};
 use std::time::Duration;
 use swf::avm1::types::SendVarsMethod;
 use url::{ParseError, Url};
@@ -39,6 +41,12 @@ pub enum NavigationMethod {
 
     /// Indicates that navigation should generate a POST request.
     Post,
+
+    /// Indicates that navigation should generate a HEAD request, which
+    /// retrieves the status and headers of a resource without its body.
+    ///
+    /// Movies can't choose this method themselves.
+    Head,
 }
 
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
@@ -100,6 +108,7 @@ impl fmt::Display for NavigationMethod {
         let method = match self {
             Self::Get => "GET",
             Self::Post => "POST",
+            Self::Head => "HEAD",
         };
         f.write_str(method)
     }
@@ -119,12 +128,26 @@ pub struct Request {
     /// The body consists of data and a mime type.
     body: Option<(Vec<u8>, String)>,
 
+    /// The contents of the request body supplied progressively, as chunks
+    /// received until the channel is closed, and its mime type.
+    body_stream: Option<(Receiver<Vec<u8>>, String)>,
+
     /// The headers for the request, as (header_name, header_value) pairs.
     /// Flash appears to iterate over an internal hash table to determine
-    /// the order of headers sent over the network. We just use an IndexMap
-    /// to give us a consistent order - hopefully, no servers depend on
-    /// the order of headers.
-    headers: IndexMap<String, String>,
+    /// the order of headers sent over the network. We just keep them in the
+    /// order they were given - hopefully, no servers depend on the order of
+    /// headers. A header may be given several times.
+    headers: Vec<(String, String)>,
+
+    /// The range of bytes of the resource to request, if not all of it.
+    range: Option<ByteRange>,
+
+    /// Allows cancelling the request while it is in progress.
+    cancellation: Option<CancellationToken>,
+
+    /// The maximum amount of time the request may take, overriding the
+    /// default of the navigator backend.
+    timeout: Option<Duration>,
 }
 
 impl Request {
@@ -134,7 +157,11 @@ impl Request {
             url,
             method: NavigationMethod::Get,
             body: None,
+            body_stream: None,
             headers: Default::default(),
+            range: None,
+            cancellation: None,
+            timeout: None,
         }
     }
 
@@ -144,7 +171,11 @@ impl Request {
             url,
             method: NavigationMethod::Post,
             body,
+            body_stream: None,
             headers: Default::default(),
+            range: None,
+            cancellation: None,
+            timeout: None,
         }
     }
 
@@ -155,7 +186,11 @@ impl Request {
             url,
             method,
             body,
+            body_stream: None,
             headers: Default::default(),
+            range: None,
+            cancellation: None,
+            timeout: None,
         }
     }
 
@@ -178,12 +213,242 @@ impl Request {
         self.body = Some(body);
     }
 
-    pub fn headers(&self) -> &IndexMap<String, String> {
+    /// Retrieve the streamed body of this request, if it has one.
+    pub fn body_stream(&self) -> Option<&(Receiver<Vec<u8>>, String)> {
+        self.body_stream.as_ref()
+    }
+
+    /// Stream the body of this request, sending the chunks received from
+    /// `chunks` as they come, rather than holding the whole body in memory.
+    ///
+    /// The body ends once `chunks` is closed. This takes precedence over
+    /// `body`, though not all navigator backends support streamed bodies.
+    pub fn set_body_stream(&mut self, chunks: Receiver<Vec<u8>>, mime: String) {
+        self.body_stream = Some((chunks, mime));
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
         &self.headers
     }
 
-    pub fn set_headers(&mut self, headers: IndexMap<String, String>) {
-        self.headers = headers;
+    pub fn set_headers(&mut self, headers: impl IntoIterator<Item = (String, String)>) {
+        self.headers = headers.into_iter().collect();
+    }
+
+    /// Add a header to this request, keeping any other values of the same
+    /// header, so that it's sent once for each value.
+    pub fn append_header(&mut self, name: String, value: String) {
+        self.headers.push((name, value));
+    }
+
+    /// Retrieve the range of bytes requested, if not the whole resource.
+    pub fn range(&self) -> Option<ByteRange> {
+        self.range
+    }
+
+    /// Request only a range of bytes of the resource.
+    ///
+    /// Servers supporting this respond with `206 Partial Content`, while
+    /// others may send the whole resource anyway. `NetStream` loaders use
+    /// this to resume downloads which were interrupted.
+    pub fn set_range(&mut self, range: ByteRange) {
+        self.range = Some(range);
+    }
+
+    /// Retrieve the token cancelling this request, if any.
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// Allow cancelling this request with `token`.
+    ///
+    /// Once cancelled, the fetch, or the reading of its body, fails with
+    /// `Error::Cancelled`.
+    pub fn set_cancellation(&mut self, token: CancellationToken) {
+        self.cancellation = Some(token);
+    }
+
+    /// Retrieve the timeout of this request, if it has its own.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Give this request its own timeout, from connecting until the whole
+    /// response has been received.
+    ///
+    /// This takes precedence over the default timeout of the navigator
+    /// backend, which applies when `None`.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+}
+
+/// A token cancelling a fetch which is in progress.
+///
+/// Clones share the same state, so that the core can keep one to cancel a
+/// request after handing it to the navigator backend.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<Mutex<CancellationState>>);
+
+#[derive(Debug, Default)]
+struct CancellationState {
+    cancelled: bool,
+    wakers: Vec<Waker>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel the request, waking up everything waiting on this token.
+    pub fn cancel(&self) {
+        let mut state = self.0.lock().expect("working cancellation lock");
+        state.cancelled = true;
+        for waker in state.wakers.drain(..) {
+            waker.wake();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.lock().expect("working cancellation lock").cancelled
+    }
+
+    /// Wait until the request is cancelled.
+    pub fn cancelled(&self) -> impl Future<Output = ()> + '_ {
+        std::future::poll_fn(|cx| {
+            let mut state = self.0.lock().expect("working cancellation lock");
+            if state.cancelled {
+                return Poll::Ready(());
+            }
+            if !state.wakers.iter().any(|waker| waker.will_wake(cx.waker())) {
+                state.wakers.push(cx.waker().clone());
+            }
+            Poll::Pending
+        })
+    }
+}
+
+/// A range of bytes of a resource, as requested with the `Range` header.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ByteRange {
+    /// The offset of the first byte.
+    pub start: u64,
+
+    /// The offset of the last byte, inclusive.
+    ///
+    /// `None` means up to the end of the resource.
+    pub end: Option<u64>,
+}
+
+impl fmt::Display for ByteRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.end {
+            Some(end) => write!(f, "bytes={}-{}", self.start, end),
+            None => write!(f, "bytes={}-", self.start),
+        }
+    }
+}
+
+/// The part of a resource contained in a `206 Partial Content` response.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ContentRange {
+    /// The offset of the first byte.
+    pub start: u64,
+
+    /// The offset of the last byte, inclusive.
+    pub end: u64,
+
+    /// The length of the whole resource, if known.
+    pub total: Option<u64>,
+}
+
+impl ContentRange {
+    /// Parse the value of a `Content-Range` header, such as `bytes 0-99/1234`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let (start, rest) = value.trim().strip_prefix("bytes ")?.split_once('-')?;
+        let (end, total) = rest.split_once('/')?;
+        let total = match total.trim() {
+            "*" => None,
+            total => Some(total.parse().ok()?),
+        };
+        Some(Self {
+            start: start.trim().parse().ok()?,
+            end: end.trim().parse().ok()?,
+            total,
+        })
+    }
+}
+
+/// A part of a resource, along with where it belongs in the resource.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ByteRangePart {
+    pub range: ContentRange,
+    pub data: Vec<u8>,
+}
+
+/// Split a `multipart/byteranges` body into its parts, given the
+/// `Content-Type` of the response, which names their boundary.
+fn parse_multipart_byteranges(
+    content_type: &str,
+    body: &[u8],
+) -> Result<Vec<ByteRangePart>, String> {
+    let boundary = content_type
+        .split(';')
+        .skip(1)
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("boundary"))
+        .map(|(_, boundary)| boundary.trim().trim_matches('"'))
+        .filter(|boundary| !boundary.is_empty())
+        .ok_or("the content type has no boundary")?;
+    let delimiter = format!("--{boundary}");
+    let delimiter = delimiter.as_bytes();
+    let find = |haystack: &[u8], needle: &[u8]| {
+        haystack
+            .windows(needle.len())
+            .position(|window| window == needle)
+    };
+
+    // Anything before the first delimiter is ignored.
+    let start = find(body, delimiter).ok_or("the body has no parts")?;
+    let mut rest = &body[start + delimiter.len()..];
+    let mut parts = vec![];
+    loop {
+        if rest.starts_with(b"--") {
+            return Ok(parts);
+        }
+        rest = rest
+            .strip_prefix(b"\r\n")
+            .ok_or("a delimiter isn't followed by a line break")?;
+
+        let headers_end = find(rest, b"\r\n\r\n").ok_or("a part has no end of headers")?;
+        let headers = std::str::from_utf8(&rest[..headers_end])
+            .map_err(|_| "the headers of a part aren't valid UTF-8")?;
+        let range = headers
+            .split("\r\n")
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.trim().eq_ignore_ascii_case("content-range"))
+            .and_then(|(_, value)| ContentRange::parse(value))
+            .ok_or("a part has no valid Content-Range")?;
+        rest = &rest[headers_end + 4..];
+
+        let length = range
+            .end
+            .checked_sub(range.start)
+            .and_then(|length| usize::try_from(length).ok())
+            .and_then(|length| length.checked_add(1))
+            .ok_or("a part has an invalid Content-Range")?;
+        let data = rest
+            .get(..length)
+            .ok_or("a part is shorter than its Content-Range")?;
+        rest = rest[length..]
+            .strip_prefix(b"\r\n")
+            .and_then(|rest| rest.strip_prefix(delimiter))
+            .ok_or("a part isn't followed by a delimiter")?;
+        parts.push(ByteRangePart {
+            range,
+            data: data.to_vec(),
+        });
     }
 }
 
@@ -200,8 +465,98 @@ pub trait SuccessResponse {
     /// The status code of the response.
     fn status(&self) -> u16;
 
+    /// The URL that was requested, before any redirects.
+    fn requested_url(&self) -> Cow<str>;
+
     /// Indicates if the request has been redirected.
-    fn redirected(&self) -> bool;
+    fn redirected(&self) -> bool {
+        self.url() != self.requested_url()
+    }
+
+    /// The headers of the response, in the order they were received.
+    ///
+    /// A header sent several times appears once for each of its values.
+    fn headers(&self) -> &[(String, String)];
+
+    /// The part of the resource contained in the body, for `206 Partial
+    /// Content` responses to requests for a range of bytes.
+    ///
+    /// `None` means the body contains the whole resource.
+    fn content_range(&self) -> Option<ContentRange> {
+        if self.status() != 206 {
+            return None;
+        }
+        let (_, value) = self
+            .headers()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-range"))?;
+        ContentRange::parse(value)
+    }
+
+    /// The character encoding of the response body, as given by the `charset`
+    /// parameter of its `Content-Type`, such as `Shift_JIS` for
+    /// `text/plain; charset=Shift_JIS`.
+    ///
+    /// `None` means the response gave no hint, in which case no particular
+    /// encoding should be assumed. Loaders decode text in this encoding, and
+    /// fall back to UTF-8 without it.
+    fn charset(&self) -> Option<String> {
+        let (_, value) = self
+            .headers()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))?;
+        value.split(';').skip(1).find_map(|parameter| {
+            let (name, value) = parameter.split_once('=')?;
+            if !name.trim().eq_ignore_ascii_case("charset") {
+                return None;
+            }
+            let value = value.trim().trim_matches('"');
+            (!value.is_empty()).then(|| value.to_string())
+        })
+    }
+
+    /// Retrieve the parts of the resource contained in the response body,
+    /// with their offsets in the resource.
+    ///
+    /// `multipart/byteranges` responses, sent when several ranges of bytes
+    /// are requested at once, are split into their parts. Other responses
+    /// contain a single part, unless their body is empty. `NetStream`
+    /// loaders use this to find where a resumed download picks up.
+    ///
+    /// This method consumes the response.
+    fn byte_ranges(self: Box<Self>) -> OwnedFuture<Vec<ByteRangePart>, Error> {
+        let multipart_type = self
+            .headers()
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case("content-type"))
+            .map(|(_, value)| value.clone())
+            .filter(|value| {
+                self.status() == 206
+                    && value
+                        .trim_start()
+                        .get(..20)
+                        .is_some_and(|mime| mime.eq_ignore_ascii_case("multipart/byteranges"))
+            });
+        let content_range = self.content_range();
+        let body = self.body();
+        Box::pin(async move {
+            let body = body.await?;
+            if let Some(content_type) = multipart_type {
+                return parse_multipart_byteranges(&content_type, &body).map_err(|reason| {
+                    Error::FetchError(format!("Malformed multipart/byteranges body: {reason}"))
+                });
+            }
+            if body.is_empty() {
+                return Ok(vec![]);
+            }
+            let range = content_range.unwrap_or(ContentRange {
+                start: 0,
+                end: body.len() as u64 - 1,
+                total: Some(body.len() as u64),
+            });
+            Ok(vec![ByteRangePart { range, data: body }])
+        })
+    }
 
     /// Read the next chunk of the response.
     ///
@@ -239,6 +594,22 @@ pub struct ErrorResponse {
 /// result of type `Result<T, E>`.
 pub type OwnedFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + 'static>>;
 
+/// How urgently a spawned future should be run, compared to other futures.
+///
+/// Backends with many futures ready at once run those of higher priority
+/// first, so that loading the main movie isn't held up by background loads.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FuturePriority {
+    /// Background work, such as loading sounds.
+    Low,
+
+    #[default]
+    Normal,
+
+    /// Work the movie can't proceed without, such as loading the root movie.
+    High,
+}
+
 /// A backend interacting with a browser environment.
 pub trait NavigatorBackend {
     /// Cause a browser navigation to a given URL.
@@ -290,6 +661,18 @@ pub trait NavigatorBackend {
     /// This seems highly limiting.
     fn spawn_future(&mut self, future: OwnedFuture<(), Error>);
 
+    /// Arrange for a future to be run, like `spawn_future`, with the given
+    /// priority.
+    ///
+    /// Backends which don't prioritize futures run it like any other.
+    fn spawn_future_with_priority(
+        &mut self,
+        future: OwnedFuture<(), Error>,
+        _priority: FuturePriority,
+    ) {
+        self.spawn_future(future);
+    }
+
     /// Handle any context specific pre-processing
     ///
     /// Changing http -> https for example. This function may alter any part of the
@@ -304,6 +687,9 @@ pub trait NavigatorBackend {
     ///
     /// Use [SocketAction::Data] to send data to AVM side.
     ///
+    /// Use [SocketAction::Written] to report how many of the bytes received
+    /// from AVM side were written to the connection.
+    ///
     /// When the Sender of the Receiver is dropped then this task should end.
     fn connect_socket(
         &mut self,
@@ -575,7 +961,6 @@ pub fn fetch_path<NavigatorType: NavigatorBackend>(
         path: PathBuf,
         open_file: Option<File>,
         status: u16,
-        redirected: bool,
     }
 
     impl SuccessResponse for LocalResponse {
@@ -593,8 +978,12 @@ pub fn fetch_path<NavigatorType: NavigatorBackend>(
             self.status
         }
 
-        fn redirected(&self) -> bool {
-            self.redirected
+        fn requested_url(&self) -> Cow<str> {
+            Cow::Borrowed(&self.url)
+        }
+
+        fn headers(&self) -> &[(String, String)] {
+            &[]
         }
 
         fn next_chunk(&mut self) -> OwnedFuture<Option<Vec<u8>>, Error> {
@@ -676,7 +1065,6 @@ pub fn fetch_path<NavigatorType: NavigatorBackend>(
             path,
             open_file: None,
             status: 0,
-            redirected: false,
         });
 
         Ok(response)
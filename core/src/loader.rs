@@ -13,7 +13,10 @@ use crate::avm2::{
     Activation as Avm2Activation, Avm2, BitmapDataObject, Domain as Avm2Domain,
     Object as Avm2Object, Value as Avm2Value,
 };
-use crate::backend::navigator::{ErrorResponse, OwnedFuture, Request, SuccessResponse};
+use crate::backend::navigator::{
+    ByteRange, ByteRangePart, CancellationToken, ErrorResponse, OwnedFuture, Request,
+    SuccessResponse,
+};
 use crate::backend::ui::DialogResultFuture;
 use crate::bitmap::bitmap_data::Color;
 use crate::bitmap::bitmap_data::{BitmapData, BitmapDataWrapper};
@@ -30,12 +33,12 @@ use crate::string::AvmString;
 use crate::tag_utils::SwfMovie;
 use crate::vminterface::Instantiator;
 use crate::{avm2_stub_method, avm2_stub_method_context};
-use encoding_rs::UTF_8;
+use encoding_rs::{Encoding, UTF_8};
 use gc_arena::{Collect, GcCell};
 use indexmap::IndexMap;
 use ruffle_render::utils::{determine_jpeg_tag_format, JpegTagFormat};
 use slotmap::{new_key_type, SlotMap};
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
 use std::fmt;
 use std::str::FromStr;
 use std::sync::{Arc, Mutex, Weak};
@@ -51,6 +54,31 @@ new_key_type! {
 /// The depth of AVM1 movies that AVM2 loads.
 const LOADER_INSERTED_AVM1_DEPTH: i32 = -0xF000;
 
+/// How many bytes of an error response body are shown in error messages.
+const ERROR_BODY_SNIPPET_SIZE: usize = 512;
+
+/// The most bytes of the body of an error response kept in
+/// [`Error::HttpNotOk`].
+pub const MAX_ERROR_BODY_SIZE: usize = 64 * 1024;
+
+/// How many times an interrupted `NetStream` download is resumed, by
+/// requesting the rest of the file, before giving up.
+const MAX_NETSTREAM_RESUMES: u32 = 3;
+
+/// Format the start of an error response body for error messages.
+fn body_snippet(body: &[u8]) -> String {
+    if body.is_empty() {
+        return String::new();
+    }
+    let snippet = String::from_utf8_lossy(&body[..body.len().min(ERROR_BODY_SNIPPET_SIZE)]);
+    let ellipsis = if body.len() > ERROR_BODY_SNIPPET_SIZE {
+        "..."
+    } else {
+        ""
+    };
+    format!(", body: {}{ellipsis}", snippet.trim())
+}
+
 /// How Ruffle should load movies.
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -183,8 +211,15 @@ pub enum Error {
     #[error("Other Loader spawned as Movie unloader")]
     NotMovieUnloader,
 
-    #[error("HTTP Status is not OK: {0} redirected: {1}")]
-    HttpNotOk(String, u16, bool, u64),
+    /// The server responded with an error status. Holds the error message,
+    /// the status code, whether the request was redirected, and the body of
+    /// the response.
+    ///
+    /// The start of the body is included in the error message, as it often
+    /// explains why the server rejected the request. Backends keep at most
+    /// [`MAX_ERROR_BODY_SIZE`] bytes of it.
+    #[error("HTTP Status is not OK: {0} redirected: {2}{}", body_snippet(.3))]
+    HttpNotOk(String, u16, bool, Vec<u8>),
 
     /// The domain could not be resolved, either because it is invalid or a DNS error occurred
     #[error("Domain resolution failure: {0}")]
@@ -246,6 +281,15 @@ impl From<crate::avm1::Error<'_>> for Error {
 /// Holds all in-progress loads for the player.
 pub struct LoadManager<'gc>(SlotMap<LoaderHandle, Loader<'gc>>);
 
+impl<'gc> Drop for LoadManager<'gc> {
+    fn drop(&mut self) {
+        // Don't keep fetching data for a player which was unloaded.
+        for (_, loader) in self.0.iter() {
+            loader.cancel();
+        }
+    }
+}
+
 unsafe impl<'gc> Collect for LoadManager<'gc> {
     fn trace(&self, cc: &gc_arena::Collection) {
         for (_, loader) in self.0.iter() {
@@ -293,8 +337,33 @@ impl<'gc> LoadManager<'gc> {
 
     /// Remove a completed loader.
     /// This is used to remove a loader after the loading or unloading process has completed.
+    ///
+    /// If the loader is still fetching its data, the fetch is cancelled.
     pub fn remove_loader(&mut self, handle: LoaderHandle) {
-        self.0.remove(handle);
+        if let Some(loader) = self.0.remove(handle) {
+            loader.cancel();
+        }
+    }
+
+    /// Cancel the loads of a `URLLoader`, as done by `URLLoader.close`.
+    ///
+    /// The loaders are removed, so that no events are fired for these loads.
+    pub fn close_url_loader(&mut self, target: Avm2Object<'gc>) {
+        let handles: Vec<_> = self
+            .0
+            .iter()
+            .filter_map(|(handle, loader)| match loader {
+                Loader::LoadURLLoader { target_object, .. }
+                    if Avm2Object::ptr_eq(*target_object, target) =>
+                {
+                    Some(handle)
+                }
+                _ => None,
+            })
+            .collect();
+        for handle in handles {
+            self.remove_loader(handle);
+        }
     }
 
     /// Retrieve a loader by handle.
@@ -460,12 +529,15 @@ impl<'gc> LoadManager<'gc> {
         &mut self,
         player: Weak<Mutex<Player>>,
         target_object: Avm2Object<'gc>,
-        request: Request,
+        mut request: Request,
         data_format: DataFormat,
     ) -> OwnedFuture<(), Error> {
+        let cancellation = CancellationToken::new();
+        request.set_cancellation(cancellation.clone());
         let loader = Loader::LoadURLLoader {
             self_handle: None,
             target_object,
+            cancellation,
         };
         let handle = self.add_loader(loader);
         let loader = self.get_loader_mut(handle).unwrap();
@@ -753,6 +825,10 @@ pub enum Loader<'gc> {
 
         /// The target `URLLoader` to load data into.
         target_object: Avm2Object<'gc>,
+
+        /// The token cancelling the fetch when the `URLLoader` is closed.
+        #[collect(require_static)]
+        cancellation: CancellationToken,
     },
 
     /// Loader that is loading an MP3 into an AVM1 Sound object.
@@ -857,6 +933,13 @@ pub enum Loader<'gc> {
 }
 
 impl<'gc> Loader<'gc> {
+    /// Cancel the fetch of this loader, if it can be cancelled.
+    fn cancel(&self) {
+        if let Loader::LoadURLLoader { cancellation, .. } = self {
+            cancellation.cancel();
+        }
+    }
+
     /// Process tags on a loaded movie.
     ///
     /// Is only callable on Movie loaders, panics otherwise. Will
@@ -923,15 +1006,16 @@ impl<'gc> Loader<'gc> {
 
     async fn wait_for_full_response(
         response: OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse>,
-    ) -> Result<(Vec<u8>, String, u16, bool), ErrorResponse> {
+    ) -> Result<(Vec<u8>, String, u16, bool, Option<String>), ErrorResponse> {
         let response = response.await?;
         let url = response.url().to_string();
         let status = response.status();
         let redirected = response.redirected();
+        let charset = response.charset();
         let body = response.body().await;
 
         match body {
-            Ok(body) => Ok((body, url, status, redirected)),
+            Ok(body) => Ok((body, url, status, redirected, charset)),
             Err(error) => Err(ErrorResponse { url, error }),
         }
     }
@@ -1076,7 +1160,7 @@ impl<'gc> Loader<'gc> {
             })?;
 
             match Self::wait_for_full_response(fetch).await {
-                Ok((body, url, _status, _redirected)) if replacing_root_movie => {
+                Ok((body, url, _status, _redirected, _charset)) if replacing_root_movie => {
                     ContentType::sniff(&body).expect(ContentType::Swf)?;
 
                     let movie = SwfMovie::from_data(&body, url.to_string(), loader_url)?;
@@ -1121,7 +1205,7 @@ impl<'gc> Loader<'gc> {
                     });
                     return Ok(());
                 }
-                Ok((body, url, status, redirected)) => {
+                Ok((body, url, status, redirected, _)) => {
                     player.lock().unwrap().mutate_with_update_context(|uc| {
                         Loader::movie_loader_data(
                             handle,
@@ -1302,7 +1386,7 @@ impl<'gc> Loader<'gc> {
                     Activation::from_stub(uc.reborrow(), ActivationIdentifier::root("[Loader]"));
 
                 match response {
-                    Ok((body, _, status, _)) => {
+                    Ok((body, _, status, _, charset)) => {
                         let length = body.len();
 
                         // Set the properties used by the getBytesTotal and getBytesLoaded methods.
@@ -1323,11 +1407,9 @@ impl<'gc> Loader<'gc> {
                         let value_data = if length == 0 {
                             Value::Undefined
                         } else {
-                            AvmString::new_utf8(
-                                activation.context.gc_context,
-                                UTF_8.decode(&body).0,
-                            )
-                            .into()
+                            let text = decode_with_charset(&body, charset.as_deref())
+                                .unwrap_or_else(|| UTF_8.decode(&body).0);
+                            AvmString::new_utf8(activation.context.gc_context, text).into()
                         };
                         let _ = that.call_method(
                             "onData".into(),
@@ -1402,12 +1484,11 @@ impl<'gc> Loader<'gc> {
                     Activation::from_stub(uc.reborrow(), ActivationIdentifier::root("[Loader]"));
 
                 match response {
-                    Ok((body, _, _, _)) => {
+                    Ok((body, _, _, _, charset)) => {
                         // Fire the parse & onLoad methods with the loaded string.
-                        let css = AvmString::new_utf8(
-                            activation.context.gc_context,
-                            UTF_8.decode(&body).0,
-                        );
+                        let text = decode_with_charset(&body, charset.as_deref())
+                            .unwrap_or_else(|| UTF_8.decode(&body).0);
+                        let css = AvmString::new_utf8(activation.context.gc_context, text);
                         let success = that
                             .call_method(
                                 "parse".into(),
@@ -1466,7 +1547,8 @@ impl<'gc> Loader<'gc> {
                 let loader = uc.load_manager.get_loader(handle);
                 let target = match loader {
                     Some(&Loader::LoadURLLoader { target_object, .. }) => target_object,
-                    // We would have already returned after the previous 'update' call
+                    // The loader was closed
+                    None => return Err(Error::Cancelled),
                     _ => unreachable!(),
                 };
 
@@ -1477,6 +1559,7 @@ impl<'gc> Loader<'gc> {
                     activation: &mut Avm2Activation<'a, 'gc>,
                     target: Avm2Object<'gc>,
                     data_format: DataFormat,
+                    charset: Option<&str>,
                 ) {
                     let data_object = match data_format {
                         DataFormat::Binary => {
@@ -1487,13 +1570,25 @@ impl<'gc> Loader<'gc> {
                             Some(bytearray.into())
                         }
                         DataFormat::Text => {
-                            let string_value =
-                                AvmString::new_utf8_bytes(activation.context.gc_context, &body);
+                            let string_value = match decode_with_charset(&body, charset) {
+                                Some(text) => {
+                                    AvmString::new_utf8(activation.context.gc_context, text)
+                                }
+                                None => {
+                                    AvmString::new_utf8_bytes(activation.context.gc_context, &body)
+                                }
+                            };
                             Some(Avm2Value::String(string_value))
                         }
                         DataFormat::Variables => {
-                            let string_value =
-                                AvmString::new_utf8_bytes(activation.context.gc_context, &body);
+                            let string_value = match decode_with_charset(&body, charset) {
+                                Some(text) => {
+                                    AvmString::new_utf8(activation.context.gc_context, text)
+                                }
+                                None => {
+                                    AvmString::new_utf8_bytes(activation.context.gc_context, &body)
+                                }
+                            };
                             activation
                                 .avm2()
                                 .classes()
@@ -1512,7 +1607,7 @@ impl<'gc> Loader<'gc> {
                 }
 
                 match response {
-                    Ok((body, _, status, redirected)) => {
+                    Ok((body, _, status, redirected, charset)) => {
                         let total_len = body.len();
 
                         // FIXME - the "open" event should be fired earlier, just before
@@ -1527,7 +1622,13 @@ impl<'gc> Loader<'gc> {
                         let open_evt =
                             Avm2EventObject::bare_default_event(&mut activation.context, "open");
                         Avm2::dispatch_event(&mut activation.context, open_evt, target);
-                        set_data(body, &mut activation, target, data_format);
+                        set_data(
+                            body,
+                            &mut activation,
+                            target,
+                            data_format,
+                            charset.as_deref(),
+                        );
 
                         // FIXME - we should fire "progress" events as we receive data, not
                         // just at the end
@@ -1574,18 +1675,17 @@ impl<'gc> Loader<'gc> {
                         Avm2::dispatch_event(uc, complete_evt, target);
                     }
                     Err(response) => {
-                        // Testing with Flash shoes that the 'data' property is cleared
-                        // when an error occurs
-
-                        set_data(Vec::new(), &mut activation, target, data_format);
+                        // Like in Flash, the 'data' property holds the body of
+                        // error responses, such as the error description of a web
+                        // service, and is cleared when there's no response at all.
+                        let (status_code, redirected, body) = match response.error {
+                            Error::HttpNotOk(_, status_code, redirected, body) => {
+                                (status_code, redirected, body)
+                            }
+                            _ => (0, false, Vec::new()),
+                        };
+                        set_data(body, &mut activation, target, data_format, None);
 
-                        let (status_code, redirected) =
-                            if let Error::HttpNotOk(_, status_code, redirected, _) = response.error
-                            {
-                                (status_code, redirected)
-                            } else {
-                                (0, false)
-                            };
                         let http_status_evt = activation
                             .avm2()
                             .classes()
@@ -1662,7 +1762,7 @@ impl<'gc> Loader<'gc> {
 
                 let success = response
                     .map_err(|e| e.error)
-                    .and_then(|(body, _, _, _)| {
+                    .and_then(|(body, _, _, _, _)| {
                         let handle = uc.audio.register_mp3(&body)?;
                         sound_object.set_sound(uc.gc_context, Some(handle));
                         let duration = uc
@@ -1723,7 +1823,7 @@ impl<'gc> Loader<'gc> {
                 };
 
                 match response {
-                    Ok((body, _, _, _)) => {
+                    Ok((body, _, _, _, _)) => {
                         let handle = uc.audio.register_mp3(&body)?;
                         if let Err(e) = sound_object
                             .as_sound_object()
@@ -1815,6 +1915,7 @@ impl<'gc> Loader<'gc> {
             .expect("Could not upgrade weak reference to player");
 
         Box::pin(async move {
+            let url = request.url().to_string();
             let fetch = player.lock().unwrap().navigator().fetch(request);
             match fetch.await {
                 Ok(mut response) => {
@@ -1836,9 +1937,79 @@ impl<'gc> Loader<'gc> {
                         Ok(())
                     })?;
 
+                    let mut loaded = 0;
+                    let mut resumes = 0;
                     loop {
                         let chunk = response.next_chunk().await;
+
+                        // Pick up an interrupted download where it stopped,
+                        // rather than losing the rest of the stream.
+                        if let Err(error) = &chunk {
+                            if loaded > 0 && resumes < MAX_NETSTREAM_RESUMES {
+                                resumes += 1;
+                                let mut request = Request::get(url.clone());
+                                request.set_range(ByteRange {
+                                    start: loaded,
+                                    end: None,
+                                });
+                                let fetch = player.lock().unwrap().navigator().fetch(request);
+                                match fetch.await {
+                                    Ok(resumed)
+                                        if resumed
+                                            .content_range()
+                                            .is_some_and(|range| range.start == loaded) =>
+                                    {
+                                        tracing::warn!(
+                                            "Resuming download of {url} at byte {loaded} after error: {error}"
+                                        );
+                                        response = resumed;
+                                        continue;
+                                    }
+                                    Ok(resumed) => {
+                                        // The server sent the whole file, or
+                                        // several ranges of it, instead.
+                                        let data = resumed
+                                            .byte_ranges()
+                                            .await
+                                            .map(|parts| bytes_from_offset(parts, loaded));
+                                        if let Ok(mut data) = data {
+                                            if !data.is_empty() {
+                                                tracing::warn!(
+                                                    "Resuming download of {url} at byte {loaded} after error: {error}"
+                                                );
+                                                player.lock().unwrap().update(|uc| {
+                                                    let loader = uc.load_manager.get_loader(handle);
+                                                    let stream = match loader {
+                                                        Some(&Loader::NetStream {
+                                                            target_stream,
+                                                            ..
+                                                        }) => target_stream,
+                                                        None => return Err(Error::Cancelled),
+                                                        _ => return Err(Error::NotNetStreamLoader),
+                                                    };
+
+                                                    stream.load_buffer(uc, &mut data);
+                                                    stream.finish_buffer(uc);
+                                                    Ok(())
+                                                })?;
+                                                break;
+                                            }
+                                        }
+                                        tracing::warn!(
+                                            "Could not resume download of {url} at byte {loaded}"
+                                        );
+                                    }
+                                    Err(_) => tracing::warn!(
+                                        "Could not resume download of {url} at byte {loaded}"
+                                    ),
+                                }
+                            }
+                        }
+
                         let is_end = matches!(chunk, Ok(None));
+                        if let Ok(Some(data)) = &chunk {
+                            loaded += data.len() as u64;
+                        }
                         player.lock().unwrap().update(|uc| {
                             let loader = uc.load_manager.get_loader(handle);
                             let stream = match loader {
@@ -2996,7 +3167,7 @@ impl<'gc> Loader<'gc> {
                             )?;
 
                             match download_res {
-                                Ok((body, _, _, _)) => {
+                                Ok((body, _, _, _, _)) => {
                                     as_broadcaster::broadcast_internal(
                                         &mut activation,
                                         target_object,
@@ -3049,7 +3220,7 @@ impl<'gc> Loader<'gc> {
                                                 "onIOError".into(),
                                             )?;
                                         }
-                                        Error::HttpNotOk(_, _, _, body_len) => {
+                                        Error::HttpNotOk(_, _, _, body) => {
                                             // If the error happens before the connection is
                                             // established, then don't invoke onOpen
                                             as_broadcaster::broadcast_internal(
@@ -3077,8 +3248,8 @@ impl<'gc> Loader<'gc> {
                                                 target_object,
                                                 &[
                                                     target_object.into(),
-                                                    body_len.into(),
-                                                    body_len.into(),
+                                                    body.len().into(),
+                                                    body.len().into(),
                                                 ],
                                                 "onProgress".into(),
                                             )?;
@@ -3302,3 +3473,33 @@ impl<'gc> Loader<'gc> {
         })
     }
 }
+
+/// Collect the bytes of a resource from `offset` onwards, out of `parts` of it,
+/// for as long as the parts cover them without gaps.
+fn bytes_from_offset(mut parts: Vec<ByteRangePart>, mut offset: u64) -> Vec<u8> {
+    parts.sort_by_key(|part| part.range.start);
+    let mut data = vec![];
+    for part in parts {
+        if part.range.end < offset {
+            continue;
+        }
+        if part.range.start > offset {
+            break;
+        }
+        let skip = (offset - part.range.start) as usize;
+        if let Some(bytes) = part.data.get(skip..) {
+            data.extend_from_slice(bytes);
+            offset = part.range.end + 1;
+        }
+    }
+    data
+}
+
+/// Decode loaded text in the encoding named by the `charset` of the response.
+///
+/// Returns `None` when the response names no encoding, or one that isn't
+/// known, leaving the text to be decoded as UTF-8.
+fn decode_with_charset<'a>(body: &'a [u8], charset: Option<&str>) -> Option<Cow<'a, str>> {
+    let encoding = Encoding::for_label(charset?.as_bytes())?;
+    Some(encoding.decode(body).0)
+}
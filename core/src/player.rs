@@ -13,7 +13,7 @@ use crate::backend::ui::FontDefinition;
 use crate::backend::{
     audio::{AudioBackend, AudioManager},
     log::LogBackend,
-    navigator::{NavigatorBackend, Request},
+    navigator::{FuturePriority, NavigatorBackend, Request},
     storage::StorageBackend,
     ui::{InputManager, MouseCursor, UiBackend},
 };
@@ -389,7 +389,9 @@ impl Player {
                 parameters,
                 on_metadata,
             );
-            context.navigator.spawn_future(future);
+            context
+                .navigator
+                .spawn_future_with_priority(future, FuturePriority::High);
         });
     }
 
@@ -36,15 +36,112 @@ struct Socket<'gc> {
     target: SocketKind<'gc>,
     sender: RefCell<AsyncSender<Vec<u8>>>,
     connected: Cell<bool>,
+
+    /// The amount of bytes sent which weren't written to the connection yet.
+    bytes_pending: Cell<usize>,
+
+    /// The amount of bytes written to the connection so far.
+    bytes_written: Cell<usize>,
+
+    /// How the received bytes are split into messages.
+    #[collect(require_static)]
+    framing: Cell<MessageFraming>,
+
+    /// The received bytes of a message which isn't complete yet.
+    #[collect(require_static)]
+    partial_message: RefCell<Vec<u8>>,
 }
 
 impl<'gc> Socket<'gc> {
-    fn new(target: SocketKind<'gc>, sender: AsyncSender<Vec<u8>>) -> Self {
+    fn new(target: SocketKind<'gc>, sender: AsyncSender<Vec<u8>>, framing: MessageFraming) -> Self {
         Self {
             target,
             sender: RefCell::new(sender),
             connected: Cell::new(false),
+            bytes_pending: Cell::new(0),
+            bytes_written: Cell::new(0),
+            framing: Cell::new(framing),
+            partial_message: RefCell::new(Vec::new()),
+        }
+    }
+}
+
+/// How the bytes received by a socket are split into messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageFraming {
+    /// Bytes aren't split at all, and are delivered as they arrive.
+    ///
+    /// This is what AS3's `Socket` does.
+    Raw,
+
+    /// Every message is terminated by a null byte.
+    ///
+    /// This is what `XMLSocket` does.
+    NullTerminated,
+
+    /// Every message is preceded by its length, as a big-endian integer.
+    LengthPrefixed(LengthPrefix),
+}
+
+/// The size of the length preceding length-prefixed messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthPrefix {
+    U8,
+    U16,
+    U32,
+}
+
+impl LengthPrefix {
+    /// The size of the prefix, in bytes.
+    pub fn size(self) -> usize {
+        match self {
+            LengthPrefix::U8 => 1,
+            LengthPrefix::U16 => 2,
+            LengthPrefix::U32 => 4,
+        }
+    }
+}
+
+impl MessageFraming {
+    /// Take all complete messages out of `buffer`.
+    ///
+    /// Bytes of an incomplete message are left in `buffer`, so that the
+    /// message can be completed once more bytes have been received.
+    pub fn take_messages(self, buffer: &mut Vec<u8>) -> Vec<Vec<u8>> {
+        let mut messages = vec![];
+        let mut consumed = 0;
+
+        match self {
+            MessageFraming::Raw => {
+                if !buffer.is_empty() {
+                    messages.push(std::mem::take(buffer));
+                }
+            }
+            MessageFraming::NullTerminated => {
+                while let Some(length) = buffer[consumed..].iter().position(|&b| b == 0) {
+                    messages.push(buffer[consumed..consumed + length].to_vec());
+                    // Skip the null byte too.
+                    consumed += length + 1;
+                }
+            }
+            MessageFraming::LengthPrefixed(prefix) => {
+                let prefix_size = prefix.size();
+                while let Some(prefix) = buffer.get(consumed..consumed + prefix_size) {
+                    let length = prefix
+                        .iter()
+                        .fold(0usize, |length, &b| (length << 8) | usize::from(b));
+                    let start = consumed + prefix_size;
+                    let Some(message) = buffer.get(start..start + length) else {
+                        break;
+                    };
+                    messages.push(message.to_vec());
+                    consumed = start + length;
+                }
+            }
         }
+
+        buffer.drain(..consumed);
+        messages
     }
 }
 
@@ -59,6 +156,11 @@ pub enum ConnectionState {
 pub enum SocketAction {
     Connect(SocketHandle, ConnectionState),
     Data(SocketHandle, Vec<u8>),
+    /// Some of the bytes sent to a socket were written to the connection.
+    ///
+    /// Backends report this so that movies can follow the progress of their
+    /// writes, as with AS3's `OutputProgressEvent`.
+    Written(SocketHandle, usize),
     Close(SocketHandle),
 }
 
@@ -98,7 +200,7 @@ impl<'gc> Sockets<'gc> {
     ) {
         let (sender, receiver) = unbounded();
 
-        let socket = Socket::new(SocketKind::Avm2(target), sender);
+        let socket = Socket::new(SocketKind::Avm2(target), sender, MessageFraming::Raw);
         let handle = self.sockets.insert(socket);
 
         // NOTE: This call will send SocketAction::Connect to sender with connection status.
@@ -132,7 +234,11 @@ impl<'gc> Sockets<'gc> {
             None => return,
         };
 
-        let socket = Socket::new(SocketKind::Avm1(target), sender);
+        let socket = Socket::new(
+            SocketKind::Avm1(target),
+            sender,
+            MessageFraming::NullTerminated,
+        );
         let handle = self.sockets.insert(socket);
 
         // NOTE: This call will send SocketAction::Connect to sender with connection status.
@@ -152,6 +258,17 @@ impl<'gc> Sockets<'gc> {
         }
     }
 
+    /// Change how the bytes received by a socket are split into messages,
+    /// such as for a binary protocol framing its messages by their length.
+    ///
+    /// Bytes of a message which isn't complete yet are split with the new
+    /// framing once more bytes are received.
+    pub fn set_framing(&mut self, handle: SocketHandle, framing: MessageFraming) {
+        if let Some(socket) = self.sockets.get(handle) {
+            socket.framing.set(framing);
+        }
+    }
+
     pub fn is_connected(&self, handle: SocketHandle) -> bool {
         if let Some(socket) = self.sockets.get(handle) {
             socket.connected.get()
@@ -161,15 +278,31 @@ impl<'gc> Sockets<'gc> {
     }
 
     pub fn send(&mut self, handle: SocketHandle, data: Vec<u8>) {
-        if let Some(Socket { sender, .. }) = self.sockets.get_mut(handle) {
+        if let Some(Socket {
+            sender,
+            bytes_pending,
+            ..
+        }) = self.sockets.get_mut(handle)
+        {
+            let len = data.len();
             // We use an unbounded socket, so this should only ever error if the channel is closed
             // (the receiver was dropped)
-            if let Err(e) = sender.borrow().try_send(data) {
-                tracing::error!("Failed to send data to socket: {:?}", e);
+            match sender.borrow().try_send(data) {
+                Ok(()) => bytes_pending.set(bytes_pending.get() + len),
+                Err(e) => tracing::error!("Failed to send data to socket: {:?}", e),
             }
         }
     }
 
+    /// The amount of bytes sent to a socket which weren't written to the
+    /// connection yet.
+    pub fn bytes_pending(&self, handle: SocketHandle) -> usize {
+        self.sockets
+            .get(handle)
+            .map(|socket| socket.bytes_pending.get())
+            .unwrap_or_default()
+    }
+
     pub fn close_all(&mut self) {
         for (_, socket) in self.sockets.drain() {
             Self::close_internal(socket);
@@ -187,21 +320,18 @@ impl<'gc> Sockets<'gc> {
             sender,
             target,
             connected: _,
+            bytes_pending: _,
+            bytes_written: _,
+            framing: _,
+            partial_message: _,
         } = socket;
 
         drop(sender); // NOTE: By dropping the sender, the reading task will close automatically.
 
         // Clear the buffers if the connection was closed.
-        match target {
-            SocketKind::Avm1(target) => {
-                let target = XmlSocket::cast(target.into()).expect("target should be XmlSocket");
-
-                target.read_buffer().clear();
-            }
-            SocketKind::Avm2(target) => {
-                target.read_buffer().clear();
-                target.write_buffer().clear();
-            }
+        if let SocketKind::Avm2(target) = target {
+            target.read_buffer().clear();
+            target.write_buffer().clear();
         }
     }
 
@@ -303,9 +433,16 @@ impl<'gc> Sockets<'gc> {
                         }
                     }
                 }
-                SocketAction::Data(handle, mut data) => {
-                    let target = match context.sockets.sockets.get(handle) {
-                        Some(socket) => socket.target,
+                SocketAction::Data(handle, data) => {
+                    // Messages may be split across several packets, so collect
+                    // the received bytes until complete messages are available.
+                    let (target, messages) = match context.sockets.sockets.get(handle) {
+                        Some(socket) => {
+                            let mut partial_message = socket.partial_message.borrow_mut();
+                            partial_message.extend(data);
+                            let messages = socket.framing.get().take_messages(&mut partial_message);
+                            (socket.target, messages)
+                        }
                         // Socket must have been closed before we could send event.
                         None => continue,
                     };
@@ -314,31 +451,33 @@ impl<'gc> Sockets<'gc> {
                         SocketKind::Avm2(target) => {
                             let mut activation = Avm2Activation::from_nothing(context.reborrow());
 
-                            let bytes_loaded = data.len();
-                            target.read_buffer().extend(data);
+                            for message in messages {
+                                let bytes_loaded = message.len();
+                                target.read_buffer().extend(message);
 
-                            let progress_evt = activation
-                                .avm2()
-                                .classes()
-                                .progressevent
-                                .construct(
-                                    &mut activation,
-                                    &[
-                                        "socketData".into(),
-                                        false.into(),
-                                        false.into(),
-                                        bytes_loaded.into(),
-                                        //NOTE: bytesTotal is not used by socketData event.
-                                        0.into(),
-                                    ],
-                                )
-                                .expect("ProgressEvent should be constructed");
-
-                            Avm2::dispatch_event(
-                                &mut activation.context,
-                                progress_evt,
-                                target.into(),
-                            );
+                                let progress_evt = activation
+                                    .avm2()
+                                    .classes()
+                                    .progressevent
+                                    .construct(
+                                        &mut activation,
+                                        &[
+                                            "socketData".into(),
+                                            false.into(),
+                                            false.into(),
+                                            bytes_loaded.into(),
+                                            //NOTE: bytesTotal is not used by socketData event.
+                                            0.into(),
+                                        ],
+                                    )
+                                    .expect("ProgressEvent should be constructed");
+
+                                Avm2::dispatch_event(
+                                    &mut activation.context,
+                                    progress_evt,
+                                    target.into(),
+                                );
+                            }
                         }
                         SocketKind::Avm1(target) => {
                             let mut activation = Avm1Activation::from_stub(
@@ -346,60 +485,62 @@ impl<'gc> Sockets<'gc> {
                                 ActivationIdentifier::root("[XMLSocket]"),
                             );
 
-                            // NOTE: This is enforced in connect_avm1() function.
-                            let xml_socket =
-                                XmlSocket::cast(target.into()).expect("target should be XmlSocket");
-
-                            // Check if the current received packet includes a null byte.
-                            if let Some((index, _)) = data.iter().enumerate().find(|(_, &b)| b == 0)
-                            {
-                                // Received payload contains a null byte, so take data from sockets read buffer and append message data ontop.
-                                let mut buffer = xml_socket
-                                    .read_buffer()
-                                    .drain(..)
-                                    .chain(data.drain(..index))
-                                    .collect::<Vec<_>>();
-
-                                // Now we loop to check for more null bytes.
-                                loop {
-                                    // Remove null byte.
-                                    data.drain(..1);
-
-                                    // Create message from the buffer.
-                                    let message =
-                                        AvmString::new_utf8_bytes(activation.gc(), &buffer);
-
-                                    // Call the event handler.
-                                    let _ = target.call_method(
-                                        "onData".into(),
-                                        &[message.into()],
-                                        &mut activation,
-                                        ExecutionReason::Special,
-                                    );
-
-                                    // Check if we have another null byte in the same payload.
-                                    if let Some((index, _)) =
-                                        data.iter().enumerate().find(|(_, &b)| b == 0)
-                                    {
-                                        // Because data in XmlSocket::read_buffer() has already been consumed
-                                        // we do not need to access it again.
-                                        buffer = data.drain(..index).collect::<Vec<_>>();
-                                    } else {
-                                        // No more messages in the payload, so exit the loop.
-                                        break;
-                                    }
-                                }
-
-                                // Check if we have leftover bytes.
-                                if !data.is_empty() {
-                                    // We had leftover bytes, so append them to XmlSocket internal read buffer,
-                                    // to be used when the next packet arrives.
-                                    xml_socket.read_buffer().extend(data);
-                                }
+                            for message in messages {
+                                let message = AvmString::new_utf8_bytes(activation.gc(), &message);
+
+                                // Call the event handler.
+                                let _ = target.call_method(
+                                    "onData".into(),
+                                    &[message.into()],
+                                    &mut activation,
+                                    ExecutionReason::Special,
+                                );
                             }
                         }
                     }
                 }
+                SocketAction::Written(handle, written) => {
+                    let (target, bytes_pending, bytes_total) =
+                        match context.sockets.sockets.get(handle) {
+                            Some(socket) => {
+                                let bytes_pending =
+                                    socket.bytes_pending.get().saturating_sub(written);
+                                let bytes_written = socket.bytes_written.get() + written;
+                                socket.bytes_pending.set(bytes_pending);
+                                socket.bytes_written.set(bytes_written);
+                                (socket.target, bytes_pending, bytes_written + bytes_pending)
+                            }
+                            // Socket must have been closed before we could send event.
+                            None => continue,
+                        };
+
+                    // XMLSocket has no way to follow the progress of writes.
+                    if let SocketKind::Avm2(target) = target {
+                        let mut activation = Avm2Activation::from_nothing(context.reborrow());
+
+                        let output_progress_evt = activation
+                            .avm2()
+                            .classes()
+                            .outputprogressevent
+                            .construct(
+                                &mut activation,
+                                &[
+                                    "outputProgress".into(),
+                                    false.into(),
+                                    false.into(),
+                                    bytes_pending.into(),
+                                    bytes_total.into(),
+                                ],
+                            )
+                            .expect("OutputProgressEvent should be constructed");
+
+                        Avm2::dispatch_event(
+                            &mut activation.context,
+                            output_progress_evt,
+                            target.into(),
+                        );
+                    }
+                }
                 SocketAction::Close(handle) => {
                     let target = match context.sockets.sockets.remove(handle) {
                         Some(socket) => {
@@ -428,12 +569,6 @@ impl<'gc> Sockets<'gc> {
                                 ActivationIdentifier::root("[XMLSocket]"),
                             );
 
-                            // Clear the read buffer if the connection was closed.
-                            let socket =
-                                XmlSocket::cast(target.into()).expect("target should be XmlSocket");
-
-                            socket.read_buffer().clear();
-
                             let _ = target.call_method(
                                 "onClose".into(),
                                 &[],
@@ -447,3 +582,64 @@ impl<'gc> Sockets<'gc> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{LengthPrefix, MessageFraming};
+
+    #[test]
+    fn take_raw_messages() {
+        let mut buffer = b"a\0b".to_vec();
+        assert_eq!(
+            MessageFraming::Raw.take_messages(&mut buffer),
+            [b"a\0b".to_vec()]
+        );
+        assert!(buffer.is_empty());
+        assert!(MessageFraming::Raw.take_messages(&mut buffer).is_empty());
+    }
+
+    #[test]
+    fn take_null_terminated_messages() {
+        let framing = MessageFraming::NullTerminated;
+
+        let mut buffer = b"first\0\0sec".to_vec();
+        assert_eq!(
+            framing.take_messages(&mut buffer),
+            [b"first".to_vec(), b"".to_vec()]
+        );
+        assert_eq!(buffer, b"sec");
+
+        buffer.extend(b"ond");
+        assert!(framing.take_messages(&mut buffer).is_empty());
+        buffer.extend(b"\0");
+        assert_eq!(framing.take_messages(&mut buffer), [b"second".to_vec()]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn take_length_prefixed_messages() {
+        let framing = MessageFraming::LengthPrefixed(LengthPrefix::U16);
+
+        let mut buffer = b"\0\x02ab\0\0\0\x03c".to_vec();
+        assert_eq!(
+            framing.take_messages(&mut buffer),
+            [b"ab".to_vec(), b"".to_vec()]
+        );
+        assert_eq!(buffer, b"\0\x03c");
+
+        buffer.extend(b"\0d");
+        assert_eq!(framing.take_messages(&mut buffer), [b"c\0d".to_vec()]);
+        assert!(buffer.is_empty());
+
+        let framing = MessageFraming::LengthPrefixed(LengthPrefix::U32);
+        let mut buffer = b"\0\0\x01\0".to_vec();
+        assert!(framing.take_messages(&mut buffer).is_empty());
+        assert_eq!(buffer.len(), 4);
+        let mut buffer = b"\x01x\x01".to_vec();
+        assert_eq!(
+            MessageFraming::LengthPrefixed(LengthPrefix::U8).take_messages(&mut buffer),
+            [b"x".to_vec()]
+        );
+        assert_eq!(buffer, b"\x01");
+    }
+}
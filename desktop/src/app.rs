@@ -141,6 +141,7 @@ impl App {
                     if let Some(mut player) = self.player.get() {
                         player.flush_shared_objects();
                     }
+                    self.player.shutdown();
                     crate::shutdown();
                     return;
                 }
@@ -485,6 +486,34 @@ impl App {
                     self.player.destroy();
                 }
 
+                winit::event::Event::UserEvent(RuffleEvent::FetchProgress {
+                    url,
+                    bytes_loaded,
+                    bytes_total,
+                }) => {
+                    // TODO: Show a progress bar while downloads are running.
+                    tracing::trace!(
+                        "Downloaded {bytes_loaded} of {bytes_total:?} bytes from {url}"
+                    );
+                }
+
+                winit::event::Event::UserEvent(RuffleEvent::LoadError { url, failure }) => {
+                    // The player already shows a dialog about the failure.
+                    tracing::error!("Couldn't load {url}: {failure}");
+                }
+
+                winit::event::Event::UserEvent(RuffleEvent::SocketConnect {
+                    host,
+                    port,
+                    allowed,
+                }) => {
+                    if allowed {
+                        tracing::info!("Movie is connecting to {host}:{port}");
+                    } else {
+                        tracing::info!("Movie was denied connecting to {host}:{port}");
+                    }
+                }
+
                 winit::event::Event::UserEvent(RuffleEvent::ExitRequested) => {
                     elwt.exit();
                     return;
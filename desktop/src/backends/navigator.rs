@@ -1,25 +1,82 @@
+use crate::custom_event::RuffleEvent;
 use rfd::{AsyncMessageDialog, MessageButtons, MessageDialog, MessageDialogResult, MessageLevel};
-use ruffle_frontend_utils::backends::navigator::NavigatorInterface;
+use ruffle_frontend_utils::backends::navigator::{
+    FetchMetrics, LoadFailure, NavigationConfirmation, NavigatorInterface,
+};
 use std::fs::File;
 use std::io;
 use std::io::ErrorKind;
 use std::path::Path;
-use url::Url;
+use url::{ParseError, Url};
+use winit::event_loop::EventLoopProxy;
 
 #[derive(Clone)]
-pub struct RfdNavigatorInterface;
+pub struct RfdNavigatorInterface {
+    pub event_loop: EventLoopProxy<RuffleEvent>,
+}
 
 impl NavigatorInterface for RfdNavigatorInterface {
-    fn confirm_website_navigation(&self, url: &Url) -> bool {
-        let message = format!("The SWF file wants to open the website {}", url);
-        // TODO: Add a checkbox with a GUI toolkit
-        MessageDialog::new()
+    fn confirm_website_navigation(&self, url: &Url) -> NavigationConfirmation {
+        const OPEN: &str = "Open";
+        const ALWAYS_OPEN: &str = "Always open";
+        const CANCEL: &str = "Cancel";
+
+        if let ("file", Ok(path)) = (url.scheme(), url.to_file_path()) {
+            // Choices about local files aren't remembered, as they have no
+            // origin to remember them for.
+            let message = format!(
+                "The SWF file wants to open the local file {}\n\nIt will be opened with the default program for it, which may run it if it's an executable.",
+                path.display()
+            );
+            let result = MessageDialog::new()
+                .set_title("Open file?")
+                .set_level(MessageLevel::Warning)
+                .set_description(message)
+                .set_buttons(MessageButtons::OkCancelCustom(
+                    OPEN.to_string(),
+                    CANCEL.to_string(),
+                ))
+                .show();
+            return NavigationConfirmation {
+                allowed: matches!(result, MessageDialogResult::Custom(choice) if choice == OPEN),
+                remember: false,
+            };
+        }
+
+        let message = format!(
+            "The SWF file wants to open the website {}\n\nChoose \"{ALWAYS_OPEN}\" to open websites of {} without asking again.",
+            url,
+            url.origin().ascii_serialization()
+        );
+        // TODO: Use a checkbox to remember denials too, once we have a GUI
+        // toolkit with one available here.
+        let result = MessageDialog::new()
             .set_title("Open website?")
             .set_level(MessageLevel::Info)
             .set_description(message)
-            .set_buttons(MessageButtons::OkCancel)
-            .show()
-            == MessageDialogResult::Ok
+            .set_buttons(MessageButtons::YesNoCancelCustom(
+                OPEN.to_string(),
+                ALWAYS_OPEN.to_string(),
+                CANCEL.to_string(),
+            ))
+            .show();
+
+        match result {
+            MessageDialogResult::Custom(choice) if choice == OPEN => NavigationConfirmation {
+                allowed: true,
+                remember: false,
+            },
+            MessageDialogResult::Custom(choice) if choice == ALWAYS_OPEN => {
+                NavigationConfirmation {
+                    allowed: true,
+                    remember: true,
+                }
+            }
+            _ => NavigationConfirmation {
+                allowed: false,
+                remember: false,
+            },
+        }
     }
 
     fn open_file(&self, path: &Path) -> io::Result<File> {
@@ -52,4 +109,64 @@ impl NavigatorInterface for RfdNavigatorInterface {
             .show()
             .await == MessageDialogResult::Yes
     }
+
+    async fn confirm_certificate_exception(&self, host: &str, error: &str) -> bool {
+        let message = format!(
+            "The TLS certificate of {host:?} is invalid ({error}).\n\nSomeone may be impersonating this host. To connect anyway for the rest of this session, click Yes.\n\nOtherwise, click No to cancel the request."
+        );
+        AsyncMessageDialog::new()
+            .set_title("Invalid certificate")
+            .set_level(MessageLevel::Warning)
+            .set_description(message)
+            .set_buttons(MessageButtons::YesNo)
+            .show()
+            .await
+            == MessageDialogResult::Yes
+    }
+
+    fn fetch_progress(&self, url: &Url, bytes_loaded: u64, bytes_total: Option<u64>) {
+        let _ = self.event_loop.send_event(RuffleEvent::FetchProgress {
+            url: url.clone(),
+            bytes_loaded,
+            bytes_total,
+        });
+    }
+
+    fn fetch_completed(&self, metrics: &FetchMetrics) {
+        tracing::info!(
+            "Fetched {} bytes from {} in {:?} (queued {:?}, first byte after {:?}, transferred in {:?})",
+            metrics.bytes,
+            metrics.url,
+            metrics.total_time,
+            metrics.queue_time,
+            metrics.time_to_first_byte,
+            metrics.transfer_time
+        );
+    }
+
+    fn navigation_failed(&self, url: &str, error: &ParseError) {
+        MessageDialog::new()
+            .set_title("Couldn't open link")
+            .set_level(MessageLevel::Warning)
+            .set_description(format!(
+                "The SWF file tried to open a link, but its URL is malformed ({error}):\n\n{url}"
+            ))
+            .set_buttons(MessageButtons::Ok)
+            .show();
+    }
+
+    fn socket_connection_attempted(&self, host: &str, port: u16, allowed: bool) {
+        let _ = self.event_loop.send_event(RuffleEvent::SocketConnect {
+            host: host.to_string(),
+            port,
+            allowed,
+        });
+    }
+
+    fn load_failed(&self, url: &Url, failure: &LoadFailure) {
+        let _ = self.event_loop.send_event(RuffleEvent::LoadError {
+            url: url.clone(),
+            failure: failure.clone(),
+        });
+    }
 }
@@ -119,10 +119,15 @@ pub struct Opt {
     pub config: std::path::PathBuf,
 
     /// Proxy to use when loading movies via URL.
+    ///
+    /// Supports `http://`, `https://`, `socks5://` and `socks5h://` proxies.
+    /// Socket connections are tunneled through `http://` and `https://` proxies,
+    /// and fail rather than bypassing other proxies.
     #[clap(long)]
     pub proxy: Option<Url>,
 
     /// Add an endpoint (`[host]:[port]`) to the socket whitelist.
+    /// The host may start with `*.` to allow all of its subdomains, and the port may be `*` to allow all ports.
     #[clap(long = "socket-allow", number_of_values = 1, action = clap::ArgAction::Append)]
     pub socket_allow: Vec<String>,
 
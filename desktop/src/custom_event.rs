@@ -1,6 +1,7 @@
 //! Custom event type for desktop ruffle
 
 use crate::player::LaunchOptions;
+use ruffle_frontend_utils::backends::navigator::LoadFailure;
 
 /// User-defined events.
 pub enum RuffleEvent {
@@ -24,4 +25,22 @@ pub enum RuffleEvent {
 
     /// The user selected an item in the right-click context menu.
     ContextMenuItemClicked(usize),
+
+    /// Part of the body of a fetched response has been downloaded.
+    FetchProgress {
+        url: url::Url,
+        bytes_loaded: u64,
+        /// `None` if the length of the body isn't known.
+        bytes_total: Option<u64>,
+    },
+
+    /// Loading the played movie failed.
+    LoadError { url: url::Url, failure: LoadFailure },
+
+    /// The movie tried to open a socket, which was `allowed` or not.
+    SocketConnect {
+        host: String,
+        port: u16,
+        allowed: bool,
+    },
 }
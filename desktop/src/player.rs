@@ -10,9 +10,9 @@ use anyhow::anyhow;
 use ruffle_core::backend::navigator::{OpenURLMode, SocketMode};
 use ruffle_core::config::Letterbox;
 use ruffle_core::events::{GamepadButton, KeyCode};
-use ruffle_core::{DefaultFont, LoadBehavior, Player, PlayerBuilder, PlayerEvent};
+use ruffle_core::{DefaultFont, LoadBehavior, Player, PlayerBuilder, PlayerEvent, SandboxType};
 use ruffle_frontend_utils::backends::executor::{AsyncExecutor, PollRequester};
-use ruffle_frontend_utils::backends::navigator::ExternalNavigatorBackend;
+use ruffle_frontend_utils::backends::navigator::{ExternalNavigatorBackend, NavigatorOptions};
 use ruffle_frontend_utils::bundle::source::BundleSourceError;
 use ruffle_frontend_utils::bundle::{Bundle, BundleError};
 use ruffle_frontend_utils::content::PlayingContent;
@@ -33,6 +33,9 @@ use url::Url;
 use winit::event_loop::EventLoopProxy;
 use winit::window::Window;
 
+/// How long pending network transfers may take to finish when exiting.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
 /// Options used when creating a Player (& passed through to a PlayerBuilder).
 /// These may be primed by command line arguments.
 #[derive(Debug, Clone)]
@@ -203,11 +206,15 @@ impl ActivePlayer {
         let (executor, future_spawner) = AsyncExecutor::new(WinitWaker(event_loop.clone()));
         let movie_url = content.initial_swf_url().clone();
         let readable_name = content.name();
+        let base_url = opt
+            .player
+            .base
+            .to_owned()
+            .unwrap_or_else(|| movie_url.clone());
+        // Like the standalone Flash Player, local movies are trusted.
+        let sandbox_type = (base_url.scheme() == "file").then_some(SandboxType::LocalTrusted);
         let navigator = ExternalNavigatorBackend::new(
-            opt.player
-                .base
-                .to_owned()
-                .unwrap_or_else(|| movie_url.clone()),
+            base_url,
             opt.player.referer.clone(),
             opt.player.cookie.clone(),
             future_spawner,
@@ -217,7 +224,15 @@ impl ActivePlayer {
             opt.socket_allowed.clone(),
             opt.tcp_connections.unwrap_or(SocketMode::Ask),
             Rc::new(content),
-            RfdNavigatorInterface,
+            RfdNavigatorInterface {
+                event_loop: event_loop.clone(),
+            },
+            NavigatorOptions {
+                use_system_proxy: true,
+                confirm_certificate_errors: true,
+                sandbox_type,
+                ..Default::default()
+            },
         );
 
         if cfg!(feature = "external_video") && preferences.openh264_enabled() {
@@ -422,6 +437,21 @@ impl PlayerController {
         self.player = None;
     }
 
+    /// Destroy the player when exiting, giving its pending network transfers
+    /// a moment to finish rather than cutting them off.
+    pub fn shutdown(&mut self) {
+        if let Some(ActivePlayer { player, executor }) = self.player.take() {
+            // Dropping the player closes its sockets, which then write out
+            // what they have pending.
+            drop(player);
+            if !executor.shutdown(SHUTDOWN_TIMEOUT) {
+                tracing::warn!(
+                    "Network transfers didn't finish within {SHUTDOWN_TIMEOUT:?} of exiting"
+                );
+            }
+        }
+    }
+
     pub fn get(&self) -> Option<MutexGuard<Player>> {
         match &self.player {
             None => None,
@@ -2,13 +2,19 @@ mod task;
 
 pub use crate::backends::executor::task::Task;
 use async_channel::{unbounded, Receiver, Sender};
-use ruffle_core::backend::navigator::OwnedFuture;
+use ruffle_core::backend::navigator::{FuturePriority, OwnedFuture};
 use ruffle_core::loader::Error;
 use slotmap::{new_key_type, SlotMap};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
 use std::future::Future;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard, Weak};
 use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::time::{Duration, Instant};
+
+/// How often pending tasks are checked for progress while shutting down.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(10);
 
 pub trait PollRequester: Clone {
     fn request_poll(&self);
@@ -28,6 +34,9 @@ struct TaskHandle<R: PollRequester> {
     /// The arena handle for a given task.
     handle: TaskKey,
 
+    /// The priority the task was spawned with.
+    priority: FuturePriority,
+
     /// The executor the task belongs to.
     ///
     /// Weak reference ensures that the executor along
@@ -37,9 +46,10 @@ struct TaskHandle<R: PollRequester> {
 
 impl<R: PollRequester> TaskHandle<R> {
     /// Construct a handle to a given task.
-    fn for_task(task: TaskKey, executor: Weak<AsyncExecutor<R>>) -> Self {
+    fn for_task(task: TaskKey, priority: FuturePriority, executor: Weak<AsyncExecutor<R>>) -> Self {
         Self {
             handle: task,
+            priority,
             executor,
         }
     }
@@ -60,7 +70,7 @@ impl<R: PollRequester> TaskHandle<R> {
     /// Wake the task this context refers to.
     fn wake(&self) {
         if let Some(executor) = self.executor.upgrade() {
-            executor.wake(self.handle, true);
+            executor.wake(self.handle, self.priority, true);
         }
     }
 
@@ -133,18 +143,33 @@ impl<R: PollRequester> TaskHandle<R> {
     );
 }
 
+/// A task waiting in the execution queue.
+///
+/// Tasks are ordered by priority, then by when they were woken.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct QueuedTask {
+    priority: FuturePriority,
+    order: Reverse<u64>,
+    key: TaskKey,
+}
+
 pub struct AsyncExecutor<R: PollRequester> {
-    /// List of all spawned tasks.
-    tasks: Mutex<SlotMap<TaskKey, Task>>,
+    /// List of all spawned tasks, with their priority.
+    tasks: Mutex<SlotMap<TaskKey, (Task, FuturePriority)>>,
 
-    /// Task execution queue represented by a channel.
+    /// Task execution queue.
     ///
-    /// In order to wake a task it should be sent to this channel,
-    /// whereas reading from this channel is used for task polling.
-    task_queue: (Sender<TaskKey>, Receiver<TaskKey>),
+    /// In order to wake a task it should be pushed to this queue, whereas
+    /// popping from it is used for task polling. Tasks of higher priority are
+    /// polled first.
+    task_queue: Mutex<BinaryHeap<QueuedTask>>,
+
+    /// How many tasks were woken so far, used to poll tasks of the same
+    /// priority in the order they were woken.
+    wake_count: AtomicU64,
 
     /// Source of tasks sent to us by the `NavigatorBackend`.
-    task_spawner: Receiver<OwnedFuture<(), Error>>,
+    task_spawner: Receiver<(OwnedFuture<(), Error>, FuturePriority)>,
 
     /// Weak reference to ourselves.
     self_ref: Weak<Self>,
@@ -165,7 +190,8 @@ impl<R: PollRequester> AsyncExecutor<R> {
         let (send, recv) = unbounded();
         let new_self = Arc::new_cyclic(|self_ref| Self {
             tasks: Mutex::new(SlotMap::with_key()),
-            task_queue: unbounded(),
+            task_queue: Default::default(),
+            wake_count: AtomicU64::new(0),
             task_spawner: recv,
             self_ref: self_ref.clone(),
             poll_requester: poll_requester.clone(),
@@ -186,14 +212,16 @@ impl<R: PollRequester> AsyncExecutor<R> {
 
         // We want only to poll as many tasks as there were at the beginning.
         // Newly added tasks will be polled later at the next iteration.
-        let tasks_to_poll = self.task_queue.1.len();
+        let tasks_to_poll = self.task_queue.lock().expect("non-poisoned queue").len();
 
         for _ in 0..tasks_to_poll {
-            let Ok(key) = self.task_queue.1.try_recv() else {
+            let Some(QueuedTask { key, .. }) =
+                self.task_queue.lock().expect("non-poisoned queue").pop()
+            else {
                 break;
             };
 
-            let Some(task) = tasks.get_mut(key) else {
+            let Some((task, priority)) = tasks.get_mut(key) else {
                 // Tried to wake a nonexistent task.
                 continue;
             };
@@ -204,7 +232,7 @@ impl<R: PollRequester> AsyncExecutor<R> {
                 continue;
             }
 
-            let handle = TaskHandle::for_task(key, self.self_ref.clone());
+            let handle = TaskHandle::for_task(key, *priority, self.self_ref.clone());
             let waker = handle.waker();
             let mut context = Context::from_waker(&waker);
 
@@ -221,37 +249,79 @@ impl<R: PollRequester> AsyncExecutor<R> {
         }
     }
 
+    /// Stop accepting new tasks, and keep polling the pending ones until they
+    /// have all completed, or `timeout` has elapsed.
+    ///
+    /// This lets in-flight transfers finish when exiting, rather than being
+    /// dropped, without hanging on a stuck connection. Returns whether all
+    /// tasks completed.
+    pub fn shutdown(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+        // Tasks which were already sent can still be received once closed.
+        self.task_spawner.close();
+
+        loop {
+            self.poll_all();
+            if self.tasks.lock().expect("non-poisoned tasks").is_empty() {
+                return true;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            // Tasks are woken from other threads, such as when their I/O is
+            // ready, rather than through the poll requester.
+            std::thread::sleep(SHUTDOWN_POLL_INTERVAL.min(deadline - now));
+        }
+    }
+
     /// Mark a task as ready to proceed.
-    fn wake(&self, task: TaskKey, poll: bool) {
-        self.task_queue.0.try_send(task).expect("wake a task");
+    fn wake(&self, task: TaskKey, priority: FuturePriority, poll: bool) {
+        let order = Reverse(self.wake_count.fetch_add(1, Ordering::SeqCst));
+        self.task_queue
+            .lock()
+            .expect("non-poisoned queue")
+            .push(QueuedTask {
+                priority,
+                order,
+                key: task,
+            });
         if poll && !self.waiting_for_poll.swap(true, Ordering::SeqCst) {
             self.poll_requester.request_poll();
         }
     }
 
-    fn insert_tasks(&self, tasks: &mut MutexGuard<SlotMap<TaskKey, Task>>) {
-        while let Ok(fut) = self.task_spawner.try_recv() {
-            let key = tasks.insert(Task::from_future(fut));
+    fn insert_tasks(&self, tasks: &mut MutexGuard<SlotMap<TaskKey, (Task, FuturePriority)>>) {
+        while let Ok((fut, priority)) = self.task_spawner.try_recv() {
+            let key = tasks.insert((Task::from_future(fut), priority));
 
             // Start executing the newly added task by waking it.
             // We do not poll here, as we are inserting tasks during a poll already.
-            self.wake(key, false);
+            self.wake(key, priority, false);
         }
     }
 }
 
 pub trait FutureSpawner {
     fn spawn(&self, future: OwnedFuture<(), Error>);
+
+    /// Spawn a future with the given priority.
+    ///
+    /// Spawners which don't prioritize futures spawn it like any other.
+    fn spawn_with_priority(&self, future: OwnedFuture<(), Error>, _priority: FuturePriority) {
+        self.spawn(future);
+    }
 }
 
 pub struct AsyncFutureSpawner<R: PollRequester> {
-    channel: Sender<OwnedFuture<(), Error>>,
+    channel: Sender<(OwnedFuture<(), Error>, FuturePriority)>,
     poll_requester: R,
 }
 
 impl<R: PollRequester> AsyncFutureSpawner<R> {
     pub fn send_and_poll(
-        channel: Sender<OwnedFuture<(), Error>>,
+        channel: Sender<(OwnedFuture<(), Error>, FuturePriority)>,
         poll_requester: R,
     ) -> AsyncFutureSpawner<R> {
         AsyncFutureSpawner {
@@ -263,9 +333,14 @@ impl<R: PollRequester> AsyncFutureSpawner<R> {
 
 impl<R: PollRequester> FutureSpawner for AsyncFutureSpawner<R> {
     fn spawn(&self, future: OwnedFuture<(), Error>) {
-        self.channel
-            .send_blocking(future)
-            .expect("working channel send");
+        self.spawn_with_priority(future, FuturePriority::Normal);
+    }
+
+    fn spawn_with_priority(&self, future: OwnedFuture<(), Error>, priority: FuturePriority) {
+        if self.channel.send_blocking((future, priority)).is_err() {
+            tracing::warn!("Not spawning a future, as the executor is shut down");
+            return;
+        }
         self.poll_requester.request_poll()
     }
 }
@@ -282,3 +357,89 @@ where
         .await
         .expect("Oneshot should succeed")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    #[derive(Clone)]
+    struct NoPollRequester;
+
+    impl PollRequester for NoPollRequester {
+        fn request_poll(&self) {}
+    }
+
+    #[test]
+    fn test_shutdown() {
+        let (executor, spawner) = AsyncExecutor::new(NoPollRequester);
+        let (sender, receiver) = unbounded();
+        let completed = Rc::new(RefCell::new(false));
+        let task_completed = completed.clone();
+        spawner.spawn(Box::pin(async move {
+            let _ = receiver.recv().await;
+            *task_completed.borrow_mut() = true;
+            Ok(())
+        }));
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            let _ = sender.send_blocking(());
+        });
+
+        assert!(executor.shutdown(Duration::from_secs(10)));
+        assert!(*completed.borrow());
+
+        // Futures spawned afterwards are dropped without being run.
+        let spawned_late = completed.clone();
+        spawner.spawn(Box::pin(async move {
+            *spawned_late.borrow_mut() = false;
+            Ok(())
+        }));
+        executor.poll_all();
+        assert!(*completed.borrow());
+    }
+
+    #[test]
+    fn test_shutdown_timeout() {
+        let (executor, spawner) = AsyncExecutor::new(NoPollRequester);
+        spawner.spawn(Box::pin(std::future::pending()));
+
+        let started = Instant::now();
+        assert!(!executor.shutdown(Duration::from_millis(50)));
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_poll_by_priority() {
+        let (executor, spawner) = AsyncExecutor::new(NoPollRequester);
+        let polled = Rc::new(RefCell::new(vec![]));
+        let priorities = [
+            FuturePriority::Low,
+            FuturePriority::Normal,
+            FuturePriority::High,
+            FuturePriority::Normal,
+        ];
+        for (id, priority) in priorities.into_iter().enumerate() {
+            let polled = polled.clone();
+            spawner.spawn_with_priority(
+                Box::pin(async move {
+                    polled.borrow_mut().push((priority, id));
+                    Ok(())
+                }),
+                priority,
+            );
+        }
+
+        executor.poll_all();
+        assert_eq!(
+            *polled.borrow(),
+            [
+                (FuturePriority::High, 2),
+                (FuturePriority::Normal, 1),
+                (FuturePriority::Normal, 3),
+                (FuturePriority::Low, 0),
+            ]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::backends::navigator::cache::stable_hash;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use url::Url;
+
+/// The file listing which file each archived URL was written to.
+const MANIFEST_NAME: &str = "manifest.txt";
+
+/// A local mirror of fetched resources, for preserving content whose servers
+/// may disappear.
+///
+/// Bodies are written under a path derived from their URL, and each one is
+/// listed in a manifest as a line with the URL and path separated by a tab.
+pub struct Archive {
+    directory: PathBuf,
+}
+
+impl Archive {
+    pub fn new(directory: PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(&directory)?;
+        Ok(Self { directory })
+    }
+
+    /// Write the body of `url` to the archive, replacing any previous one.
+    pub fn store(&self, url: &Url, body: &[u8]) {
+        let path = archive_path(url);
+        if let Err(e) = self.write(url, &path, body) {
+            tracing::warn!("Couldn't archive {url} to {path:?}: {e}");
+        }
+    }
+
+    fn write(&self, url: &Url, path: &Path, body: &[u8]) -> io::Result<()> {
+        let full_path = self.directory.join(path);
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(full_path, body)?;
+
+        let line = format!("{}\t{}\n", url, path.to_string_lossy().replace('\\', "/"));
+        File::options()
+            .create(true)
+            .append(true)
+            .open(self.directory.join(MANIFEST_NAME))?
+            .write_all(line.as_bytes())
+    }
+}
+
+/// The path of the archived body of `url`, relative to the archive.
+///
+/// The path mirrors the host and path of the URL. Characters which may not
+/// be valid in file names are replaced, and the query, if any, is appended
+/// as a hash so that URLs only differing in it don't overwrite each other.
+fn archive_path(url: &Url) -> PathBuf {
+    let mut path = PathBuf::from(sanitize(&match url.port() {
+        Some(port) => format!("{}_{port}", url.host_str().unwrap_or_default()),
+        None => url.host_str().unwrap_or_default().to_string(),
+    }));
+
+    let mut segments: Vec<_> = url.path_segments().into_iter().flatten().collect();
+    let file_name = match segments.pop() {
+        Some(file_name) if !file_name.is_empty() => sanitize(file_name),
+        _ => "index".to_string(),
+    };
+    path.extend(segments.into_iter().map(sanitize));
+
+    match url.query() {
+        Some(query) => {
+            let hash = stable_hash(query.as_bytes());
+            path.push(format!("{file_name}_{hash:016x}"));
+        }
+        None => path.push(file_name),
+    }
+    path
+}
+
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '-' | '_' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(url: &str) -> String {
+        archive_path(&Url::parse(url).unwrap())
+            .to_string_lossy()
+            .replace('\\', "/")
+    }
+
+    #[test]
+    fn test_archive_path() {
+        assert_eq!(
+            path("https://example.com/games/game.swf"),
+            "example.com/games/game.swf"
+        );
+        assert_eq!(path("http://example.com:8080/"), "example.com_8080/index");
+        assert_eq!(
+            path("https://example.com/a%20b/c:d"),
+            "example.com/a_20b/c_d"
+        );
+        assert_ne!(
+            path("https://example.com/data.php?level=1"),
+            path("https://example.com/data.php?level=2")
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use reqwest::header::{self, HeaderMap};
+use std::fs::{self, File};
+use std::io;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use url::Url;
+
+/// A hash of `bytes` which stays the same across runs and platforms, for
+/// naming files.
+///
+/// This is FNV-1a, which unlike the standard library's hasher is stable.
+pub fn stable_hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// An on-disk cache of fetched resources.
+///
+/// Responses are stored with their `ETag` and `Last-Modified` validators, so
+/// that they can be revalidated with conditional requests. When the cache
+/// grows larger than its maximum size, the least recently used entries are
+/// evicted.
+pub struct HttpCache {
+    directory: PathBuf,
+
+    /// The maximum total size of cached bodies, in bytes.
+    max_size: Option<u64>,
+}
+
+/// The validators of a cached response.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    /// Get the validators of a response, if it may be cached at all.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let no_store = headers
+            .get_all(header::CACHE_CONTROL)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|directive| directive.trim().eq_ignore_ascii_case("no-store"));
+        if no_store {
+            return None;
+        }
+
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &header::HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let validators = Self {
+            etag: header(header::ETAG),
+            last_modified: header(header::LAST_MODIFIED),
+        };
+        if validators == Self::default() {
+            // Without validators, we can't know whether the entry is fresh.
+            return None;
+        }
+        Some(validators)
+    }
+}
+
+impl HttpCache {
+    pub fn new(directory: PathBuf, max_size: Option<u64>) -> io::Result<Self> {
+        fs::create_dir_all(&directory)?;
+        Ok(Self {
+            directory,
+            max_size,
+        })
+    }
+
+    /// Look up the validators of the cached response for `url`.
+    pub fn lookup(&self, url: &Url) -> Option<Validators> {
+        let (metadata_path, body_path) = self.paths(url);
+        let metadata = fs::read_to_string(metadata_path).ok()?;
+        let mut lines = metadata.lines();
+
+        // Different URLs may share the same hash.
+        if lines.next()? != url.as_str() || !body_path.exists() {
+            return None;
+        }
+        let mut value = || {
+            lines
+                .next()
+                .filter(|line| !line.is_empty())
+                .map(str::to_string)
+        };
+        Some(Validators {
+            etag: value(),
+            last_modified: value(),
+        })
+    }
+
+    /// Read the body of the cached response for `url`.
+    pub fn read_body(&self, url: &Url) -> io::Result<Vec<u8>> {
+        let (_, body_path) = self.paths(url);
+        let body = fs::read(&body_path)?;
+
+        // The modification time tracks when entries were last used.
+        if let Err(e) = File::options()
+            .write(true)
+            .open(&body_path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+        {
+            tracing::warn!("Couldn't update cache entry {body_path:?}: {e}");
+        }
+        Ok(body)
+    }
+
+    /// Store a response, evicting old entries if the cache grows too large.
+    pub fn store(&self, url: &Url, validators: &Validators, body: &[u8]) {
+        if self
+            .max_size
+            .is_some_and(|max_size| body.len() as u64 > max_size)
+        {
+            return;
+        }
+
+        let (metadata_path, body_path) = self.paths(url);
+        let metadata = format!(
+            "{}\n{}\n{}\n",
+            url,
+            validators.etag.as_deref().unwrap_or_default(),
+            validators.last_modified.as_deref().unwrap_or_default()
+        );
+        let result = fs::write(&body_path, body).and_then(|()| fs::write(metadata_path, metadata));
+        if let Err(e) = result {
+            tracing::warn!("Couldn't cache response of {url}: {e}");
+            return;
+        }
+
+        if let Err(e) = self.evict() {
+            tracing::warn!("Couldn't evict cache entries: {e}");
+        }
+    }
+
+    /// Remove the least recently used entries until the cache fits its
+    /// maximum size.
+    fn evict(&self) -> io::Result<()> {
+        let Some(max_size) = self.max_size else {
+            return Ok(());
+        };
+
+        let mut entries = vec![];
+        let mut total_size = 0;
+        for entry in fs::read_dir(&self.directory)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "body")
+            {
+                let metadata = fs::metadata(&path)?;
+                total_size += metadata.len();
+                entries.push((metadata.modified()?, metadata.len(), path));
+            }
+        }
+
+        entries.sort();
+        for (_, size, path) in entries {
+            if total_size <= max_size {
+                break;
+            }
+            fs::remove_file(path.with_extension("meta"))?;
+            fs::remove_file(path)?;
+            total_size -= size;
+        }
+        Ok(())
+    }
+
+    /// The paths of the metadata and body files of the entry for `url`.
+    fn paths(&self, url: &Url) -> (PathBuf, PathBuf) {
+        let hash = stable_hash(url.as_str().as_bytes());
+        let path = self.directory.join(format!("{hash:016x}"));
+        (path.with_extension("meta"), path.with_extension("body"))
+    }
+}
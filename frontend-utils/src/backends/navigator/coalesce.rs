@@ -0,0 +1,121 @@
+use crate::backends::navigator::fetch::{Response, ResponseBody};
+use crate::backends::navigator::memory_cache::CachedResponse;
+use futures::future::{FutureExt, Shared};
+use ruffle_core::backend::navigator::{ErrorResponse, OwnedFuture, SuccessResponse};
+use ruffle_core::loader::Error;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+use url::Url;
+
+/// Identifies identical requests: their URL and headers.
+pub type RequestKey = (Url, Vec<(String, String)>);
+
+type SharedFetch = Shared<OwnedFuture<CachedResponse, SharedError>>;
+
+/// Fetches which are in progress, so that identical requests made in the
+/// meantime share their response rather than being sent again.
+///
+/// The shared response is read in full before being handed to every request.
+#[derive(Default)]
+pub struct InFlightRequests {
+    requests: RefCell<HashMap<RequestKey, SharedFetch>>,
+}
+
+impl InFlightRequests {
+    /// Share the response of the fetch already in progress for `key`, or
+    /// `start` one if there's none.
+    pub fn fetch(
+        self: &Rc<Self>,
+        key: RequestKey,
+        start: impl FnOnce() -> OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse>,
+    ) -> OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse> {
+        let requested_url = key.0.to_string();
+        let existing = self.requests.borrow().get(&key).cloned();
+        let shared = match existing {
+            Some(shared) => shared,
+            None => {
+                let response = start();
+                let requests = self.clone();
+                let fetch_key = key.clone();
+                let fetch: OwnedFuture<_, _> = Box::pin(async move {
+                    let result = read_response(response).await;
+                    requests.requests.borrow_mut().remove(&fetch_key);
+                    result
+                });
+                let shared = fetch.shared();
+                self.requests.borrow_mut().insert(key, shared.clone());
+                shared
+            }
+        };
+
+        Box::pin(async move {
+            match shared.await {
+                Ok(cached) => {
+                    let response: Box<dyn SuccessResponse> = Box::new(Response {
+                        url: cached.url,
+                        response_body: ResponseBody::File(Ok(cached.body)),
+                        status: cached.status,
+                        requested_url,
+                        headers: cached.headers,
+                    });
+                    Ok(response)
+                }
+                Err(error) => Err(ErrorResponse {
+                    url: requested_url,
+                    error: error.into(),
+                }),
+            }
+        })
+    }
+}
+
+async fn read_response(
+    response: OwnedFuture<Box<dyn SuccessResponse>, ErrorResponse>,
+) -> Result<CachedResponse, SharedError> {
+    let response = response.await.map_err(|response| response.error)?;
+    let url = response.url().into_owned();
+    let status = response.status();
+    let headers = response.headers().to_vec();
+    let body = response.body().await?;
+    Ok(CachedResponse {
+        url,
+        status,
+        headers,
+        body,
+    })
+}
+
+/// The error of a shared fetch, which unlike `Error` can be cloned for each
+/// of its requests.
+#[derive(Clone)]
+enum SharedError {
+    HttpNotOk(String, u16, bool, Vec<u8>),
+    InvalidDomain(String),
+    Fetch(String),
+}
+
+impl From<Error> for SharedError {
+    fn from(error: Error) -> Self {
+        match error {
+            Error::HttpNotOk(message, status, redirected, body) => {
+                SharedError::HttpNotOk(message, status, redirected, body)
+            }
+            Error::InvalidDomain(domain) => SharedError::InvalidDomain(domain),
+            Error::FetchError(message) => SharedError::Fetch(message),
+            error => SharedError::Fetch(error.to_string()),
+        }
+    }
+}
+
+impl From<SharedError> for Error {
+    fn from(error: SharedError) -> Self {
+        match error {
+            SharedError::HttpNotOk(message, status, redirected, body) => {
+                Error::HttpNotOk(message, status, redirected, body)
+            }
+            SharedError::InvalidDomain(domain) => Error::InvalidDomain(domain),
+            SharedError::Fetch(message) => Error::FetchError(message),
+        }
+    }
+}
@@ -0,0 +1,91 @@
+use cookie_store::{CookieStore, RawCookie};
+use reqwest::header::HeaderValue;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use url::Url;
+
+/// A cookie jar shared by all requests of a navigator.
+///
+/// Cookies are scoped by domain and path, and can optionally be persisted
+/// to a file so that they survive between sessions.
+pub struct CookieJar {
+    store: Mutex<CookieStore>,
+
+    /// The file persistent cookies are loaded from and saved to, if any.
+    path: Option<PathBuf>,
+}
+
+impl CookieJar {
+    /// Create a cookie jar, loading previously saved cookies from `path`.
+    pub fn new(path: Option<PathBuf>) -> Self {
+        let store = path
+            .as_ref()
+            .and_then(|path| match File::open(path) {
+                Ok(file) => match CookieStore::load_json(BufReader::new(file)) {
+                    Ok(store) => Some(store),
+                    Err(e) => {
+                        tracing::warn!("Couldn't load cookies from {path:?}: {e}");
+                        None
+                    }
+                },
+                // There's nothing to load yet if the file doesn't exist.
+                Err(_) => None,
+            })
+            .unwrap_or_default();
+
+        Self {
+            store: Mutex::new(store),
+            path,
+        }
+    }
+
+    /// Add a cookie, as if it was set by a response from `url`.
+    pub fn add_cookie_str(&self, cookie: &str, url: &Url) {
+        let mut store = self.store.lock().expect("working cookie lock");
+        if let Err(e) = store.parse(cookie, url) {
+            tracing::warn!("Couldn't add cookie {cookie}: {e}");
+        }
+    }
+
+    fn save(&self, store: &CookieStore) {
+        let Some(path) = &self.path else {
+            return;
+        };
+
+        let result = File::create(path)
+            .map_err(|e| e.into())
+            .and_then(|file| store.save_json(&mut BufWriter::new(file)));
+        if let Err(e) = result {
+            tracing::warn!("Couldn't save cookies to {path:?}: {e}");
+        }
+    }
+}
+
+impl reqwest::cookie::CookieStore for CookieJar {
+    fn set_cookies(&self, cookie_headers: &mut dyn Iterator<Item = &HeaderValue>, url: &Url) {
+        let cookies = cookie_headers.filter_map(|header| {
+            let header = header.to_str().ok()?;
+            RawCookie::parse(header).ok().map(RawCookie::into_owned)
+        });
+
+        let mut store = self.store.lock().expect("working cookie lock");
+        store.store_response_cookies(cookies, url);
+        self.save(&store);
+    }
+
+    fn cookies(&self, url: &Url) -> Option<HeaderValue> {
+        let store = self.store.lock().expect("working cookie lock");
+        let cookies = store
+            .get_request_values(url)
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        if cookies.is_empty() {
+            return None;
+        }
+        HeaderValue::from_str(&cookies).ok()
+    }
+}
@@ -0,0 +1,120 @@
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::collections::HashMap;
+use std::io;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// A cache of DNS lookups, shared by HTTP requests and socket connections.
+///
+/// Entries expire after a fixed time to live, so that changes to DNS records
+/// eventually take effect.
+#[derive(Clone)]
+pub struct DnsCache {
+    ttl: Duration,
+    entries: Arc<Mutex<HashMap<String, Entry>>>,
+}
+
+struct Entry {
+    addresses: Vec<IpAddr>,
+    expires: Instant,
+}
+
+impl DnsCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    /// Resolve the addresses of `host`, reusing a previous lookup if it
+    /// hasn't expired yet.
+    pub async fn lookup(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let addresses = match self.cached(host) {
+            Some(addresses) => addresses,
+            None => {
+                let addresses: Vec<_> = async_net::resolve((host, 0))
+                    .await?
+                    .into_iter()
+                    .map(|address| address.ip())
+                    .collect();
+                self.entries.lock().expect("working DNS cache lock").insert(
+                    host.to_string(),
+                    Entry {
+                        addresses: addresses.clone(),
+                        expires: Instant::now() + self.ttl,
+                    },
+                );
+                addresses
+            }
+        };
+        Ok(addresses
+            .into_iter()
+            .map(|address| SocketAddr::new(address, port))
+            .collect())
+    }
+
+    fn cached(&self, host: &str) -> Option<Vec<IpAddr>> {
+        let mut entries = self.entries.lock().expect("working DNS cache lock");
+        let now = Instant::now();
+        entries.retain(|_, entry| entry.expires > now);
+        entries.get(host).map(|entry| entry.addresses.clone())
+    }
+}
+
+impl Resolve for DnsCache {
+    fn resolve(&self, name: Name) -> Resolving {
+        let cache = self.clone();
+        Box::pin(async move {
+            let addresses = cache.lookup(name.as_str(), 0).await?;
+            Ok(Box::new(addresses.into_iter()) as Addrs)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    const HOST: &str = "ruffle.invalid";
+
+    fn insert(cache: &DnsCache, expires: Instant) {
+        cache.entries.lock().unwrap().insert(
+            HOST.to_string(),
+            Entry {
+                addresses: vec![IpAddr::from([192, 0, 2, 1])],
+                expires,
+            },
+        );
+    }
+
+    #[test]
+    fn test_cached_lookup() {
+        let cache = DnsCache::new(Duration::from_secs(60));
+        insert(&cache, Instant::now() + Duration::from_secs(60));
+
+        let addresses = block_on(cache.lookup(HOST, 1234)).unwrap();
+        assert_eq!(addresses, [SocketAddr::from(([192, 0, 2, 1], 1234))]);
+    }
+
+    #[test]
+    fn test_expired_lookup() {
+        let cache = DnsCache::new(Duration::from_secs(60));
+        insert(&cache, Instant::now());
+
+        // The expired entry is dropped, and the name doesn't resolve.
+        assert!(block_on(cache.lookup(HOST, 1234)).is_err());
+        assert!(cache.entries.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_lookup_is_cached() {
+        let cache = DnsCache::new(Duration::from_secs(60));
+
+        let addresses = block_on(cache.lookup("localhost", 1234)).unwrap();
+        assert!(addresses.iter().all(|address| address.ip().is_loopback()));
+        assert!(cache.entries.lock().unwrap().contains_key("localhost"));
+    }
+}
@@ -1,7 +1,17 @@
+use crate::backends::navigator::throttle::Throttle;
+use futures_lite::FutureExt;
 use reqwest::Response as ReqwestResponse;
-use ruffle_core::backend::navigator::{OwnedFuture, SuccessResponse};
+use ring::digest;
+use ruffle_core::backend::navigator::{CancellationToken, OwnedFuture, SuccessResponse};
 use ruffle_core::loader::Error;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// The minimum time between two progress reports of a download.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A callback receiving the whole body of a response.
+pub type BodyCallback = Box<dyn FnOnce(&[u8]) + Send>;
 
 pub enum ResponseBody {
     /// The response's body comes from a file.
@@ -12,14 +22,231 @@ pub enum ResponseBody {
     /// This has to be stored in shared ownership so that we can return
     /// owned futures. A synchronous lock is used here as we do not
     /// expect contention on this lock.
-    Network(Arc<Mutex<Option<ReqwestResponse>>>),
+    Network(Arc<Mutex<Option<NetworkBody>>>),
+}
+
+/// A response body that is being read from the network.
+pub struct NetworkBody {
+    response: ReqwestResponse,
+
+    /// The amount of body bytes received so far.
+    received: u64,
+
+    /// The maximum amount of body bytes we are willing to receive.
+    max_size: Option<u64>,
+
+    /// The length of the body declared by the response, if any.
+    bytes_total: Option<u64>,
+
+    /// Called with the amount of bytes received and the expected total.
+    on_progress: Box<dyn Fn(u64, Option<u64>) + Send>,
+
+    /// When progress was last reported.
+    last_progress: Option<Instant>,
+
+    /// Called with the whole body once it has been received.
+    on_complete: Vec<BodyCallback>,
+
+    /// A copy of the body received so far, kept for `on_complete`.
+    received_body: Vec<u8>,
+
+    /// Limits how fast the body is received.
+    throttle: Throttle,
+
+    /// Cancels the reading of the body.
+    cancellation: Option<CancellationToken>,
+
+    /// Hashes the body as it's received, along with the SHA-256 digest it
+    /// must have.
+    integrity: Option<(digest::Context, [u8; 32])>,
+}
+
+impl NetworkBody {
+    pub fn new(
+        response: ReqwestResponse,
+        max_size: Option<u64>,
+        on_progress: Box<dyn Fn(u64, Option<u64>) + Send>,
+    ) -> Self {
+        Self {
+            bytes_total: response.content_length(),
+            response,
+            received: 0,
+            max_size,
+            on_progress,
+            last_progress: None,
+            on_complete: vec![],
+            received_body: vec![],
+            throttle: Throttle::default(),
+            cancellation: None,
+            integrity: None,
+        }
+    }
+
+    /// Limit how fast the body is received.
+    pub fn throttle(mut self, throttle: Throttle) -> Self {
+        self.throttle = throttle;
+        self
+    }
+
+    /// Stop reading the body once `cancellation` is cancelled.
+    pub fn cancellation(mut self, cancellation: Option<CancellationToken>) -> Self {
+        self.cancellation = cancellation;
+        self
+    }
+
+    /// Fail reading the body if it doesn't have the SHA-256 digest
+    /// `expected`.
+    pub fn integrity(mut self, expected: Option<[u8; 32]>) -> Self {
+        self.integrity = expected.map(|expected| (digest::Context::new(&digest::SHA256), expected));
+        self
+    }
+
+    /// Keep a copy of the body, to be passed to `on_complete` once it has
+    /// been received entirely.
+    ///
+    /// Several callbacks may be added, which are called in order.
+    pub fn on_complete(mut self, on_complete: BodyCallback) -> Self {
+        self.on_complete.push(on_complete);
+        self
+    }
+
+    /// Read the rest of the body.
+    pub async fn read_to_end(mut self) -> Result<Vec<u8>, Error> {
+        let mut body = vec![];
+        while let Some(chunk) = self.chunk().await? {
+            body.extend(chunk);
+        }
+        Ok(body)
+    }
+
+    /// Read the body up to its end or its first `limit` bytes, whichever
+    /// comes first.
+    pub async fn read_up_to(mut self, limit: usize) -> Result<Vec<u8>, Error> {
+        let mut body = vec![];
+        while body.len() < limit {
+            let Some(chunk) = self.chunk().await? else {
+                break;
+            };
+            body.extend(chunk);
+        }
+        body.truncate(limit);
+        Ok(body)
+    }
+
+    /// Read the next chunk of the body, enforcing the size limit and the
+    /// declared length.
+    async fn chunk(&mut self) -> Result<Option<Vec<u8>>, Error> {
+        let response = &mut self.response;
+        let read = async move {
+            response
+                .chunk()
+                .await
+                .map_err(|e| Error::FetchError(e.to_string()))
+        };
+        let chunk = match &self.cancellation {
+            Some(cancellation) => {
+                read.or(async {
+                    cancellation.cancelled().await;
+                    Err(Error::Cancelled)
+                })
+                .await
+            }
+            None => read.await,
+        };
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            // The connection was lost before the whole body was received.
+            Err(Error::FetchError(e)) if self.is_truncated() => {
+                return Err(Error::FetchError(format!(
+                    "{}: {e}",
+                    self.truncated_message()
+                )));
+            }
+            Err(e) => return Err(e),
+        };
+
+        if let Some(chunk) = &chunk {
+            self.throttle.download(chunk.len()).await;
+            self.received += chunk.len() as u64;
+            if let Some(max_size) = self.max_size {
+                if self.received > max_size {
+                    return Err(Error::FetchError(format!(
+                        "Response body exceeds the maximum size of {max_size} bytes"
+                    )));
+                }
+            }
+            if let Some(bytes_total) = self.bytes_total {
+                if self.received > bytes_total {
+                    return Err(Error::FetchError(format!(
+                        "Response body is longer than its declared length of {bytes_total} bytes"
+                    )));
+                }
+            }
+        } else if self.is_truncated() {
+            return Err(Error::FetchError(self.truncated_message()));
+        }
+
+        match (&chunk, &mut self.integrity) {
+            (Some(chunk), Some((context, _))) => context.update(chunk),
+            (Some(_), None) => {}
+            (None, _) => {
+                if let Some((context, expected)) = self.integrity.take() {
+                    if context.finish().as_ref() != expected {
+                        return Err(Error::FetchError(
+                            "Response body doesn't match its expected SHA-256 digest".to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let now = Instant::now();
+        let report_due = match self.last_progress {
+            Some(last) => now - last >= PROGRESS_INTERVAL,
+            None => true,
+        };
+        if chunk.is_none() || report_due {
+            self.last_progress = Some(now);
+            (self.on_progress)(self.received, self.bytes_total);
+        }
+
+        match &chunk {
+            Some(chunk) if !self.on_complete.is_empty() => {
+                self.received_body.extend_from_slice(chunk)
+            }
+            Some(_) => {}
+            None => {
+                let body = std::mem::take(&mut self.received_body);
+                for on_complete in std::mem::take(&mut self.on_complete) {
+                    on_complete(&body);
+                }
+            }
+        }
+
+        Ok(chunk.map(|chunk| chunk.to_vec()))
+    }
+
+    /// Whether less of the body was received than its declared length.
+    fn is_truncated(&self) -> bool {
+        self.bytes_total
+            .is_some_and(|bytes_total| self.received < bytes_total)
+    }
+
+    fn truncated_message(&self) -> String {
+        format!(
+            "Response body was truncated after {} of its {} bytes",
+            self.received,
+            self.bytes_total.unwrap_or_default()
+        )
+    }
 }
 
 pub struct Response {
     pub url: String,
     pub response_body: ResponseBody,
     pub status: u16,
-    pub redirected: bool,
+    pub requested_url: String,
+    pub headers: Vec<(String, String)>,
 }
 
 impl SuccessResponse for Response {
@@ -34,15 +261,13 @@ impl SuccessResponse for Response {
                 Box::pin(async move { file.map_err(|e| Error::FetchError(e.to_string())) })
             }
             ResponseBody::Network(response) => Box::pin(async move {
-                Ok(response
+                let response = response
                     .lock()
                     .expect("working lock during fetch body read")
                     .take()
-                    .expect("Body cannot already be consumed")
-                    .bytes()
-                    .await
-                    .map_err(|e| Error::FetchError(e.to_string()))?
-                    .to_vec())
+                    .expect("Body cannot already be consumed");
+
+                response.read_to_end().await
             }),
         }
     }
@@ -51,8 +276,12 @@ impl SuccessResponse for Response {
         self.status
     }
 
-    fn redirected(&self) -> bool {
-        self.redirected
+    fn requested_url(&self) -> std::borrow::Cow<str> {
+        std::borrow::Cow::Borrowed(&self.requested_url)
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
     }
 
     #[allow(clippy::await_holding_lock)]
@@ -83,18 +312,11 @@ impl SuccessResponse for Response {
                         ));
                     }
 
-                    let result = lock
-                        .expect("desktop network lock")
+                    lock.expect("desktop network lock")
                         .as_mut()
                         .expect("Body cannot already be consumed")
                         .chunk()
-                        .await;
-
-                    match result {
-                        Ok(Some(bytes)) => Ok(Some(bytes.to_vec())),
-                        Ok(None) => Ok(None),
-                        Err(e) => Err(Error::FetchError(e.to_string())),
-                    }
+                        .await
                 })
             }
         }
@@ -105,8 +327,8 @@ impl SuccessResponse for Response {
             ResponseBody::File(file) => Ok(file.as_ref().map(|file| file.len() as u64).ok()),
             ResponseBody::Network(response) => {
                 let lock = response.lock().expect("no recursive locks");
-                let response = lock.as_ref().expect("Body cannot already be consumed");
-                Ok(response.content_length())
+                let body = lock.as_ref().expect("Body cannot already be consumed");
+                Ok(body.response.content_length())
             }
         }
     }
@@ -0,0 +1,213 @@
+use reqwest::header::{self, HeaderMap};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use url::Url;
+
+/// A short-lived in-memory cache of responses, for movies fetching the same
+/// resources over and over.
+///
+/// Responses are only kept for as long as their `Cache-Control: max-age`
+/// allows. When the cache grows larger than its maximum size, the least
+/// recently used entries are evicted.
+pub struct MemoryCache {
+    /// The maximum total size of cached bodies, in bytes.
+    max_size: u64,
+
+    entries: Mutex<Entries>,
+}
+
+#[derive(Default)]
+struct Entries {
+    responses: HashMap<Url, Entry>,
+
+    /// The total size of the cached bodies, in bytes.
+    size: u64,
+}
+
+struct Entry {
+    response: CachedResponse,
+    expires: Instant,
+    last_used: Instant,
+}
+
+/// A response served from the cache.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CachedResponse {
+    /// The final URL of the response, after redirects.
+    pub url: String,
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl MemoryCache {
+    pub fn new(max_size: u64) -> Self {
+        Self {
+            max_size,
+            entries: Default::default(),
+        }
+    }
+
+    /// Get the response for `url`, if it's cached and still fresh.
+    pub fn get(&self, url: &Url) -> Option<CachedResponse> {
+        let mut entries = self.entries.lock().expect("working memory cache lock");
+        let now = Instant::now();
+        entries.remove_expired(now);
+        let entry = entries.responses.get_mut(url)?;
+        entry.last_used = now;
+        Some(entry.response.clone())
+    }
+
+    /// Store the response for `url`, if it may be cached for some time,
+    /// evicting old entries if the cache grows too large.
+    pub fn store(&self, url: &Url, headers: &HeaderMap, response: CachedResponse) {
+        let Some(ttl) = freshness_lifetime(headers) else {
+            return;
+        };
+        let size = response.body.len() as u64;
+        if size > self.max_size {
+            return;
+        }
+
+        let mut entries = self.entries.lock().expect("working memory cache lock");
+        let now = Instant::now();
+        entries.remove_expired(now);
+        entries.remove(url);
+        while entries.size + size > self.max_size {
+            let least_recently_used = entries
+                .responses
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+            match least_recently_used {
+                Some(url) => entries.remove(&url),
+                None => break,
+            }
+        }
+
+        entries.size += size;
+        entries.responses.insert(
+            url.clone(),
+            Entry {
+                response,
+                expires: now + ttl,
+                last_used: now,
+            },
+        );
+    }
+}
+
+impl Entries {
+    fn remove(&mut self, url: &Url) {
+        if let Some(entry) = self.responses.remove(url) {
+            self.size -= entry.response.body.len() as u64;
+        }
+    }
+
+    fn remove_expired(&mut self, now: Instant) {
+        let mut size = self.size;
+        self.responses.retain(|_, entry| {
+            let fresh = entry.expires > now;
+            if !fresh {
+                size -= entry.response.body.len() as u64;
+            }
+            fresh
+        });
+        self.size = size;
+    }
+}
+
+/// How long a response may be reused, following its `Cache-Control: max-age`
+/// and `Age` headers.
+fn freshness_lifetime(headers: &HeaderMap) -> Option<Duration> {
+    let mut max_age = None;
+    for directive in headers
+        .get_all(header::CACHE_CONTROL)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+    {
+        let directive = directive.trim();
+        if directive.eq_ignore_ascii_case("no-store") || directive.eq_ignore_ascii_case("no-cache")
+        {
+            return None;
+        }
+        if let Some((name, value)) = directive.split_once('=') {
+            if name.trim().eq_ignore_ascii_case("max-age") {
+                max_age = value.trim().trim_matches('"').parse::<u64>().ok();
+            }
+        }
+    }
+
+    let age = headers
+        .get(header::AGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse::<u64>().ok())
+        .unwrap_or_default();
+    max_age
+        .and_then(|max_age| max_age.checked_sub(age))
+        .filter(|lifetime| *lifetime > 0)
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn headers(cache_control: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_str(cache_control).unwrap(),
+        );
+        headers
+    }
+
+    fn response(body: &[u8]) -> CachedResponse {
+        CachedResponse {
+            url: String::new(),
+            status: 200,
+            headers: vec![],
+            body: body.to_vec(),
+        }
+    }
+
+    #[test]
+    fn test_freshness_lifetime() {
+        assert_eq!(
+            freshness_lifetime(&headers("public, max-age=60")),
+            Some(Duration::from_secs(60))
+        );
+        assert_eq!(freshness_lifetime(&headers("max-age=60, no-store")), None);
+        assert_eq!(freshness_lifetime(&headers("max-age=0")), None);
+        assert_eq!(freshness_lifetime(&HeaderMap::new()), None);
+
+        let mut aged = headers("max-age=60");
+        aged.insert(header::AGE, HeaderValue::from_static("50"));
+        assert_eq!(freshness_lifetime(&aged), Some(Duration::from_secs(10)));
+    }
+
+    #[test]
+    fn test_memory_cache_eviction() {
+        let cache = MemoryCache::new(8);
+        let url = |path| {
+            Url::parse("https://example.com/")
+                .unwrap()
+                .join(path)
+                .unwrap()
+        };
+        let headers = headers("max-age=60");
+
+        cache.store(&url("a"), &headers, response(b"aaaa"));
+        cache.store(&url("b"), &headers, response(b"bbbb"));
+        // Using "a" makes "b" the least recently used entry.
+        assert_eq!(cache.get(&url("a")), Some(response(b"aaaa")));
+        cache.store(&url("c"), &headers, response(b"cccc"));
+
+        assert_eq!(cache.get(&url("a")), Some(response(b"aaaa")));
+        assert_eq!(cache.get(&url("b")), None);
+        assert_eq!(cache.get(&url("c")), Some(response(b"cccc")));
+    }
+}
@@ -0,0 +1,183 @@
+use crate::backends::executor::spawn_tokio;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use url::{Origin, Url};
+
+/// A cross-domain policy file, listing the domains whose movies may load data
+/// from a host.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrossDomainPolicy {
+    domains: Vec<String>,
+}
+
+impl CrossDomainPolicy {
+    /// Parse a `crossdomain.xml` file.
+    ///
+    /// Only `allow-access-from` elements are taken into account, unless the
+    /// policy disables itself with `permitted-cross-domain-policies="none"`.
+    pub fn parse(xml: &str) -> Self {
+        if element_attributes(xml, "site-control")
+            .filter_map(|element| attribute(element, "permitted-cross-domain-policies"))
+            .any(|policies| policies == "none")
+        {
+            return Self::default();
+        }
+
+        let domains = element_attributes(xml, "allow-access-from")
+            .filter_map(|element| attribute(element, "domain"))
+            .map(|domain| domain.to_ascii_lowercase())
+            .collect();
+        Self { domains }
+    }
+
+    /// Check whether movies from `host` may load data under this policy.
+    pub fn allows(&self, host: &str) -> bool {
+        let host = host.to_ascii_lowercase();
+        self.domains.iter().any(|domain| {
+            if domain == "*" {
+                return true;
+            }
+            match domain.strip_prefix("*.") {
+                Some(suffix) => {
+                    host == suffix
+                        || host
+                            .strip_suffix(suffix)
+                            .is_some_and(|subdomain| subdomain.ends_with('.'))
+                }
+                None => host == *domain,
+            }
+        })
+    }
+}
+
+/// The attributes of all elements named `name`, as unparsed strings.
+fn element_attributes<'a>(xml: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    xml.split('<').skip(1).filter_map(move |element| {
+        let element = element.split('>').next()?;
+        let attributes = element.strip_prefix(name)?;
+        attributes
+            .starts_with(|c: char| c.is_ascii_whitespace() || c == '/')
+            .then_some(attributes)
+    })
+}
+
+/// The value of the attribute `name`, within the attributes of an element.
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    while let Some(position) = rest.find(name) {
+        let is_name = rest[..position].ends_with(|c: char| c.is_ascii_whitespace());
+        rest = &rest[position + name.len()..];
+        if let (true, Some(value)) = (is_name, rest.trim_start().strip_prefix('=')) {
+            let value = value.trim_start();
+            let quote = value.chars().next().filter(|c| *c == '"' || *c == '\'')?;
+            return value[1..].split(quote).next();
+        }
+    }
+    None
+}
+
+/// Cross-domain policies fetched from hosts, by origin.
+pub struct PolicyCache {
+    /// How long fetched policies are used before being fetched again.
+    ttl: Duration,
+
+    entries: RefCell<HashMap<Origin, (Instant, CrossDomainPolicy)>>,
+}
+
+impl PolicyCache {
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Default::default(),
+        }
+    }
+
+    /// Check whether movies from `host` may load data from `url`, fetching
+    /// the policy of its origin if it isn't cached yet.
+    ///
+    /// A policy which can't be fetched allows nothing, like in Flash Player.
+    pub async fn allows(&self, client: &reqwest::Client, url: &Url, host: &str) -> bool {
+        let origin = url.origin();
+        if let Some((fetched, policy)) = self.entries.borrow().get(&origin) {
+            if fetched.elapsed() < self.ttl {
+                return policy.allows(host);
+            }
+        }
+
+        let policy = match url.join("/crossdomain.xml") {
+            Ok(policy_url) => fetch_policy(client, policy_url).await,
+            Err(_) => CrossDomainPolicy::default(),
+        };
+        let allowed = policy.allows(host);
+        self.entries
+            .borrow_mut()
+            .insert(origin, (Instant::now(), policy));
+        allowed
+    }
+}
+
+async fn fetch_policy(client: &reqwest::Client, url: Url) -> CrossDomainPolicy {
+    let response = match spawn_tokio(client.get(url.clone()).send()).await {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            tracing::warn!("Couldn't fetch policy file {url}: {}", response.status());
+            return CrossDomainPolicy::default();
+        }
+        Err(e) => {
+            tracing::warn!("Couldn't fetch policy file {url}: {e}");
+            return CrossDomainPolicy::default();
+        }
+    };
+    match spawn_tokio(response.text()).await {
+        Ok(xml) => CrossDomainPolicy::parse(&xml),
+        Err(e) => {
+            tracing::warn!("Couldn't read policy file {url}: {e}");
+            CrossDomainPolicy::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_policy() {
+        let policy = CrossDomainPolicy::parse(
+            r#"<?xml version="1.0"?>
+<!DOCTYPE cross-domain-policy SYSTEM "http://www.adobe.com/xml/dtds/cross-domain-policy.dtd">
+<cross-domain-policy>
+    <allow-access-from domain="www.Example.com" />
+    <allow-access-from to-ports="*" domain='*.example.org'/>
+    <allow-http-request-headers-from domain="*" headers="*"/>
+</cross-domain-policy>"#,
+        );
+
+        assert!(policy.allows("www.example.com"));
+        assert!(!policy.allows("example.com"));
+        assert!(policy.allows("example.org"));
+        assert!(policy.allows("a.b.example.org"));
+        assert!(!policy.allows("notexample.org"));
+        assert!(!policy.allows("ruffle.rs"));
+    }
+
+    #[test]
+    fn test_parse_wildcard_policy() {
+        let policy = CrossDomainPolicy::parse(
+            r#"<cross-domain-policy><allow-access-from domain="*"/></cross-domain-policy>"#,
+        );
+        assert!(policy.allows("ruffle.rs"));
+    }
+
+    #[test]
+    fn test_parse_disabled_policy() {
+        let policy = CrossDomainPolicy::parse(
+            r#"<cross-domain-policy>
+    <site-control permitted-cross-domain-policies="none"/>
+    <allow-access-from domain="*"/>
+</cross-domain-policy>"#,
+        );
+        assert!(!policy.allows("ruffle.rs"));
+    }
+}
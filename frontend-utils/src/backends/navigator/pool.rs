@@ -0,0 +1,61 @@
+use futures::{AsyncRead, AsyncReadExt, FutureExt};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+/// Idle connections by host and port, with when they became idle.
+type IdleConnections<S> = HashMap<(String, u16), Vec<(Instant, S)>>;
+
+/// Idle socket connections kept open for reuse, by host and port.
+///
+/// Connections which aren't reused within a short window are considered
+/// stale and closed.
+pub struct SocketPool<S> {
+    /// How long an idle connection may wait for reuse.
+    max_idle: Duration,
+
+    connections: RefCell<IdleConnections<S>>,
+}
+
+impl<S: AsyncRead + Unpin> SocketPool<S> {
+    pub fn new(max_idle: Duration) -> Self {
+        Self {
+            max_idle,
+            connections: Default::default(),
+        }
+    }
+
+    /// Take an idle connection to `host:port`, if there's one still usable.
+    pub fn take(&self, host: &str, port: u16) -> Option<S> {
+        let mut connections = self.connections.borrow_mut();
+        self.evict_stale(&mut connections);
+
+        let idle = connections.get_mut(&(host.to_string(), port))?;
+        while let Some((_, mut stream)) = idle.pop() {
+            // A readable connection was either closed by the server, or
+            // received data meant for its previous user.
+            if stream.read(&mut [0]).now_or_never().is_none() {
+                return Some(stream);
+            }
+        }
+        None
+    }
+
+    /// Keep an idle connection to `host:port` for reuse.
+    pub fn put(&self, host: String, port: u16, stream: S) {
+        let mut connections = self.connections.borrow_mut();
+        self.evict_stale(&mut connections);
+
+        connections
+            .entry((host, port))
+            .or_default()
+            .push((Instant::now(), stream));
+    }
+
+    fn evict_stale(&self, connections: &mut IdleConnections<S>) {
+        connections.retain(|_, idle| {
+            idle.retain(|(since, _)| since.elapsed() < self.max_idle);
+            !idle.is_empty()
+        });
+    }
+}
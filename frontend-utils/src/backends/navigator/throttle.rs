@@ -0,0 +1,117 @@
+use async_io::Timer;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Limits the rate at which bytes are transferred.
+///
+/// Clones share the same limit, so that it applies to all of their transfers
+/// together.
+#[derive(Clone)]
+struct RateLimiter {
+    bytes_per_second: u64,
+
+    /// When the bytes transferred so far will have been paid for.
+    paid_until: Arc<Mutex<Instant>>,
+}
+
+impl RateLimiter {
+    fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second,
+            paid_until: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Wait until the transfer of `bytes` fits within the limit.
+    async fn consume(&self, bytes: usize) {
+        let delay = {
+            let mut paid_until = self.paid_until.lock().expect("working throttle lock");
+            let now = Instant::now();
+            let cost = Duration::from_secs_f64(bytes as f64 / self.bytes_per_second as f64);
+            *paid_until = (*paid_until).max(now) + cost;
+            paid_until.saturating_duration_since(now)
+        };
+        if !delay.is_zero() {
+            Timer::after(delay).await;
+        }
+    }
+}
+
+/// Bandwidth limits, shared by all requests and sockets of a backend.
+#[derive(Clone, Default)]
+pub struct Throttle {
+    download: Option<RateLimiter>,
+    upload: Option<RateLimiter>,
+}
+
+impl Throttle {
+    /// Create a throttle with the given limits in bytes per second, `None`
+    /// meaning unlimited.
+    pub fn new(download: Option<u64>, upload: Option<u64>) -> Self {
+        let limiter = |rate: Option<u64>| rate.filter(|&rate| rate > 0).map(RateLimiter::new);
+        Self {
+            download: limiter(download),
+            upload: limiter(upload),
+        }
+    }
+
+    /// Wait as long as needed after receiving `bytes`.
+    pub async fn download(&self, bytes: usize) {
+        if let Some(download) = &self.download {
+            download.consume(bytes).await;
+        }
+    }
+
+    /// Wait as long as needed after sending `bytes`.
+    pub async fn upload(&self, bytes: usize) {
+        if let Some(upload) = &self.upload {
+            upload.consume(bytes).await;
+        }
+    }
+}
+
+/// Limits the rate of requests to each host, while allowing short bursts.
+#[derive(Clone)]
+pub struct HostRateLimiter {
+    /// The time between two requests to a host, once its burst is used up.
+    interval: Duration,
+
+    /// How far ahead of its schedule a host may be.
+    burst_tolerance: Duration,
+
+    /// When the next request to each host is scheduled, following the
+    /// generic cell rate algorithm.
+    schedules: Arc<Mutex<HashMap<String, Instant>>>,
+}
+
+impl HostRateLimiter {
+    /// Allow `requests_per_second` to each host, of which up to `burst` may be
+    /// made at once.
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        let interval = Duration::from_secs_f64(1.0 / requests_per_second);
+        Self {
+            interval,
+            burst_tolerance: interval.saturating_mul(burst.saturating_sub(1)),
+            schedules: Default::default(),
+        }
+    }
+
+    /// Wait until a request to `host` fits within the limit.
+    pub async fn wait(&self, host: &str) {
+        let delay = {
+            let mut schedules = self.schedules.lock().expect("working rate limit lock");
+            let now = Instant::now();
+            // Hosts which are back on schedule don't need to be remembered.
+            schedules.retain(|_, scheduled| *scheduled > now);
+
+            let scheduled = schedules.entry(host.to_string()).or_insert(now);
+            let allowed_at = scheduled.checked_sub(self.burst_tolerance).unwrap_or(now);
+            *scheduled += self.interval;
+            allowed_at.saturating_duration_since(now)
+        };
+        if !delay.is_zero() {
+            Timer::after(delay).await;
+        }
+    }
+}
@@ -0,0 +1,392 @@
+use crate::backends::navigator::throttle::Throttle;
+use crate::backends::navigator::SocketStatistics;
+use async_channel::{Receiver, Sender};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use futures::future::select;
+use futures::io::BufReader;
+use futures::stream::{self, StreamExt};
+use futures::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use ring::digest;
+use ring::rand::{SecureRandom, SystemRandom};
+use ruffle_core::socket::{SocketAction, SocketHandle};
+use std::cell::Cell;
+use std::io::{self, ErrorKind};
+use url::{Position, Url};
+
+/// Appended to the key of a handshake to compute the accepted key.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// The maximum size of the response to a handshake.
+const MAX_HANDSHAKE_SIZE: usize = 8192;
+
+/// The maximum size of a message received from the server.
+const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+
+const OPCODE_CONTINUATION: u8 = 0x0;
+const OPCODE_TEXT: u8 = 0x1;
+const OPCODE_BINARY: u8 = 0x2;
+const OPCODE_CLOSE: u8 = 0x8;
+const OPCODE_PING: u8 = 0x9;
+const OPCODE_PONG: u8 = 0xa;
+
+/// Parse the host of a socket connecting to a WebSocket endpoint, such as
+/// `ws://gateway.example.com/game`.
+///
+/// `None` means the host isn't a WebSocket URL. The port of the socket is
+/// ignored, URLs without a port using the default port of their scheme.
+pub fn websocket_url(host: &str) -> Option<Result<Url, String>> {
+    let scheme = host.split_once("://")?.0;
+    if !scheme.eq_ignore_ascii_case("ws") && !scheme.eq_ignore_ascii_case("wss") {
+        return None;
+    }
+    Some(
+        Url::parse(host)
+            .map_err(|e| e.to_string())
+            .and_then(|url| match url.host_str() {
+                Some(_) => Ok(url),
+                None => Err("the URL has no host".to_string()),
+            }),
+    )
+}
+
+/// Open a WebSocket connection to `url` over `stream`.
+///
+/// The returned stream may hold the beginning of the first messages, read
+/// along with the response to the handshake.
+pub async fn handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: S,
+    url: &Url,
+) -> io::Result<BufReader<S>> {
+    let mut key = [0; 16];
+    fill_random(&mut key)?;
+    let key = STANDARD.encode(key);
+    let request = format!(
+        "GET {} HTTP/1.1\r\nHost: {}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\nSec-WebSocket-Key: {key}\r\nSec-WebSocket-Version: 13\r\n\r\n",
+        &url[Position::BeforePath..Position::AfterQuery],
+        &url[Position::BeforeHost..Position::AfterPort],
+    );
+    let mut stream = BufReader::new(stream);
+    stream.get_mut().write_all(request.as_bytes()).await?;
+    stream.get_mut().flush().await?;
+
+    let mut status_line = String::new();
+    let mut accept = None;
+    let mut size = 0;
+    loop {
+        let mut line = String::new();
+        let read = (&mut stream)
+            .take((MAX_HANDSHAKE_SIZE - size) as u64)
+            .read_line(&mut line)
+            .await?;
+        if read == 0 {
+            return Err(invalid_data(
+                "the handshake response is incomplete or too large",
+            ));
+        }
+        size += read;
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if status_line.is_empty() {
+            status_line = line.to_string();
+        } else if let Some((name, value)) = line.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("sec-websocket-accept") {
+                accept = Some(value.trim().to_string());
+            }
+        }
+    }
+
+    if status_line.split_whitespace().nth(1) != Some("101") {
+        return Err(invalid_data(format!(
+            "the server refused the handshake with \"{status_line}\""
+        )));
+    }
+    if accept.as_deref() != Some(&accepted_key(&key)) {
+        return Err(invalid_data("the server didn't accept the handshake key"));
+    }
+    Ok(stream)
+}
+
+/// The key a server accepting a handshake with `key` responds with.
+pub fn accepted_key(key: &str) -> String {
+    let hash = digest::digest(
+        &digest::SHA1_FOR_LEGACY_USE_ONLY,
+        format!("{key}{HANDSHAKE_GUID}").as_bytes(),
+    );
+    STANDARD.encode(hash)
+}
+
+/// Exchange messages between a WebSocket connection and the AVM side until
+/// either end closes it.
+///
+/// Each write of the AVM side is sent as a binary message, and each message
+/// received is passed on whole.
+pub async fn run_websocket<S: AsyncRead + AsyncWrite + Unpin>(
+    connection: BufReader<S>,
+    handle: SocketHandle,
+    receiver: Receiver<Vec<u8>>,
+    sender: Sender<SocketAction>,
+    throttle: Throttle,
+    statistics: &Cell<SocketStatistics>,
+) {
+    let (read_half, mut write_half) = connection.split();
+    // Pings are answered by the writing half.
+    let (pong_sender, pong_receiver) = async_channel::unbounded();
+    let download_throttle = throttle.clone();
+    let sender2 = sender.clone();
+
+    let read = std::pin::pin!(async move {
+        let mut messages = MessageReader::new(read_half);
+        loop {
+            match messages.next().await {
+                Ok(Incoming::Message(data)) => {
+                    download_throttle.download(data.len()).await;
+                    statistics.set(SocketStatistics {
+                        bytes_received: statistics.get().bytes_received + data.len() as u64,
+                        ..statistics.get()
+                    });
+                    let _ = sender.try_send(SocketAction::Data(handle, data));
+                }
+                Ok(Incoming::Ping(payload)) => {
+                    let _ = pong_sender.try_send(payload);
+                }
+                Ok(Incoming::Close) | Err(_) => {
+                    let _ = sender.try_send(SocketAction::Close(handle));
+                    break;
+                }
+            }
+        }
+    });
+
+    let write = std::pin::pin!(async move {
+        // `None` once the AVM side closed the socket.
+        let mut outgoing = std::pin::pin!(stream::select(
+            receiver
+                .map(|data| Some((OPCODE_BINARY, data)))
+                .chain(stream::once(async { None })),
+            pong_receiver.map(|payload| Some((OPCODE_PONG, payload))),
+        ));
+        while let Some(Some((opcode, payload))) = outgoing.next().await {
+            let written = async {
+                write_half
+                    .write_all(&encode_frame(opcode, &payload)?)
+                    .await?;
+                write_half.flush().await
+            };
+            if written.await.is_err() {
+                let _ = sender2.try_send(SocketAction::Close(handle));
+                return;
+            }
+            if opcode == OPCODE_BINARY {
+                throttle.upload(payload.len()).await;
+                statistics.set(SocketStatistics {
+                    bytes_sent: statistics.get().bytes_sent + payload.len() as u64,
+                    ..statistics.get()
+                });
+                let _ = sender2.try_send(SocketAction::Written(handle, payload.len()));
+            }
+        }
+        if let Ok(frame) = encode_frame(OPCODE_CLOSE, &[]) {
+            let _ = write_half.write_all(&frame).await;
+        }
+        let _ = write_half.close().await;
+    });
+
+    select(read, write).await;
+}
+
+/// Encode a single frame, masked with a random key as required of clients.
+fn encode_frame(opcode: u8, payload: &[u8]) -> io::Result<Vec<u8>> {
+    let mut mask = [0; 4];
+    fill_random(&mut mask)?;
+    Ok(encode_masked_frame(opcode, payload, mask))
+}
+
+fn encode_masked_frame(opcode: u8, payload: &[u8], mask: [u8; 4]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(payload.len() + 14);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        length @ 0..=125 => frame.push(0x80 | length as u8),
+        length @ 126..=0xffff => {
+            frame.push(0x80 | 126);
+            frame.extend((length as u16).to_be_bytes());
+        }
+        length => {
+            frame.push(0x80 | 127);
+            frame.extend((length as u64).to_be_bytes());
+        }
+    }
+    frame.extend(mask);
+    frame.extend(
+        payload
+            .iter()
+            .zip(mask.iter().cycle())
+            .map(|(byte, mask)| byte ^ mask),
+    );
+    frame
+}
+
+/// What the server sent.
+#[derive(Debug, PartialEq, Eq)]
+enum Incoming {
+    Message(Vec<u8>),
+    Ping(Vec<u8>),
+    Close,
+}
+
+/// Reads messages, which may be fragmented into several frames.
+struct MessageReader<R> {
+    reader: R,
+
+    /// The message being received, if its last fragment hasn't come yet.
+    partial: Option<Vec<u8>>,
+}
+
+impl<R: AsyncRead + Unpin> MessageReader<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            partial: None,
+        }
+    }
+
+    async fn next(&mut self) -> io::Result<Incoming> {
+        loop {
+            let (is_final, opcode, payload) = self.frame().await?;
+            let message = match opcode {
+                OPCODE_PING => return Ok(Incoming::Ping(payload)),
+                OPCODE_PONG => continue,
+                OPCODE_CLOSE => return Ok(Incoming::Close),
+                OPCODE_TEXT | OPCODE_BINARY if self.partial.is_none() => payload,
+                OPCODE_CONTINUATION => match self.partial.take() {
+                    Some(mut message) => {
+                        message.extend(payload);
+                        message
+                    }
+                    None => return Err(invalid_data("unexpected continuation frame")),
+                },
+                _ => return Err(invalid_data(format!("unexpected frame opcode {opcode}"))),
+            };
+            if message.len() > MAX_MESSAGE_SIZE {
+                return Err(invalid_data("the message is too large"));
+            }
+            if is_final {
+                return Ok(Incoming::Message(message));
+            }
+            self.partial = Some(message);
+        }
+    }
+
+    /// Read a frame, returning whether it's the last of its message, its
+    /// opcode and its payload.
+    async fn frame(&mut self) -> io::Result<(bool, u8, Vec<u8>)> {
+        let mut header = [0; 2];
+        self.reader.read_exact(&mut header).await?;
+        let is_final = header[0] & 0x80 != 0;
+        let opcode = header[0] & 0x0f;
+        let is_masked = header[1] & 0x80 != 0;
+        let length = match header[1] & 0x7f {
+            126 => {
+                let mut length = [0; 2];
+                self.reader.read_exact(&mut length).await?;
+                u64::from(u16::from_be_bytes(length))
+            }
+            127 => {
+                let mut length = [0; 8];
+                self.reader.read_exact(&mut length).await?;
+                u64::from_be_bytes(length)
+            }
+            length => u64::from(length),
+        };
+        if length > MAX_MESSAGE_SIZE as u64 {
+            return Err(invalid_data("the message is too large"));
+        }
+
+        // Servers shouldn't mask frames, but unmasking them costs nothing.
+        let mut mask = [0; 4];
+        if is_masked {
+            self.reader.read_exact(&mut mask).await?;
+        }
+        let mut payload = vec![0; length as usize];
+        self.reader.read_exact(&mut payload).await?;
+        if is_masked {
+            for (byte, mask) in payload.iter_mut().zip(mask.iter().cycle()) {
+                *byte ^= mask;
+            }
+        }
+        Ok((is_final, opcode, payload))
+    }
+}
+
+fn fill_random(bytes: &mut [u8]) -> io::Result<()> {
+    SystemRandom::new()
+        .fill(bytes)
+        .map_err(|_| io::Error::other("couldn't generate random bytes"))
+}
+
+fn invalid_data(reason: impl Into<String>) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::executor::block_on;
+
+    #[test]
+    fn test_websocket_url() {
+        let url = |host| websocket_url(host).map(|url| url.map(String::from));
+        assert_eq!(
+            url("ws://example.com:8080/game"),
+            Some(Ok("ws://example.com:8080/game".to_string()))
+        );
+        assert_eq!(
+            url("WSS://example.com:443/"),
+            Some(Ok("wss://example.com/".to_string()))
+        );
+        assert!(matches!(url("ws://"), Some(Err(_))));
+        assert_eq!(url("example.com"), None);
+        assert_eq!(url("http://example.com"), None);
+    }
+
+    #[test]
+    fn test_accepted_key() {
+        // The example of RFC 6455.
+        assert_eq!(
+            accepted_key("dGhlIHNhbXBsZSBub25jZQ=="),
+            "s3pPLMBiTxaQ9kYGzzhZRbK+xOo="
+        );
+    }
+
+    #[test]
+    fn test_encode_masked_frame() {
+        assert_eq!(
+            encode_masked_frame(OPCODE_TEXT, b"Hello", [0x37, 0xfa, 0x21, 0x3d]),
+            [0x81, 0x85, 0x37, 0xfa, 0x21, 0x3d, 0x7f, 0x9f, 0x4d, 0x51, 0x58]
+        );
+        let frame = encode_masked_frame(OPCODE_BINARY, &[0; 256], [0; 4]);
+        assert_eq!(frame[..4], [0x82, 0xfe, 0x01, 0x00]);
+        assert_eq!(frame.len(), 4 + 4 + 256);
+    }
+
+    #[test]
+    fn test_read_fragmented_message() {
+        let frames: &[u8] = &[
+            0x01, 0x03, b'H', b'e', b'l', // A first fragment.
+            0x89, 0x00, // An interleaved ping.
+            0x80, 0x02, b'l', b'o', // The last fragment.
+            0x88, 0x00, // Closing.
+        ];
+        let mut messages = MessageReader::new(frames);
+        block_on(async {
+            assert_eq!(messages.next().await.unwrap(), Incoming::Ping(vec![]));
+            assert_eq!(
+                messages.next().await.unwrap(),
+                Incoming::Message(b"Hello".to_vec())
+            );
+            assert_eq!(messages.next().await.unwrap(), Incoming::Close);
+        });
+    }
+}
@@ -0,0 +1,93 @@
+use crate::bundle::source::zip::ZipSource;
+use crate::bundle::source::BundleSourceImpl;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use url::Url;
+
+/// ZIP archives which files were loaded from, kept open so that loading the
+/// rest of their files doesn't read their central directory again.
+#[derive(Default)]
+pub struct ZipArchives {
+    archives: RefCell<HashMap<PathBuf, Rc<ZipSource<File>>>>,
+}
+
+impl ZipArchives {
+    /// Read the file at `entry` in the archive at `path`, opening the archive
+    /// with `open_file` if it isn't open yet.
+    pub fn read(
+        &self,
+        path: &Path,
+        entry: &str,
+        open_file: impl FnOnce(&Path) -> io::Result<File>,
+    ) -> io::Result<Vec<u8>> {
+        let existing = self.archives.borrow().get(path).cloned();
+        let archive = match existing {
+            Some(archive) => archive,
+            None => {
+                let archive = ZipSource::open(open_file(path)?)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                let archive = Rc::new(archive);
+                self.archives
+                    .borrow_mut()
+                    .insert(path.to_owned(), archive.clone());
+                archive
+            }
+        };
+
+        let mut contents = vec![];
+        archive.read_file(entry)?.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+}
+
+/// Split a `file:` URL to a file inside a ZIP archive, such as
+/// `file:///game.zip!/assets/sprite.png`, into the URL of the archive and
+/// the path of the file inside it.
+///
+/// Any query or fragment is left out of the archive URL.
+pub fn split_zip_url(url: &Url) -> Option<(Url, String)> {
+    const SEPARATOR: &str = ".zip!/";
+    let path = url.path();
+    let end = path.to_ascii_lowercase().find(SEPARATOR)? + ".zip".len();
+    let entry = urlencoding::decode(&path[end + "!/".len()..]).ok()?;
+
+    let mut archive_url = url.clone();
+    archive_url.set_path(&path[..end]);
+    archive_url.set_query(None);
+    archive_url.set_fragment(None);
+    Some((archive_url, entry.into_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn split(url: &str) -> Option<(String, String)> {
+        split_zip_url(&Url::parse(url).unwrap())
+            .map(|(archive_url, entry)| (archive_url.to_string(), entry))
+    }
+
+    #[test]
+    fn test_split_zip_url() {
+        assert_eq!(
+            split("file:///games/game.zip!/assets/sprite%20sheet.png?v=1"),
+            Some((
+                "file:///games/game.zip".to_string(),
+                "assets/sprite sheet.png".to_string()
+            ))
+        );
+        assert_eq!(
+            split("file:///games/GAME.ZIP!/movie.swf"),
+            Some((
+                "file:///games/GAME.ZIP".to_string(),
+                "movie.swf".to_string()
+            ))
+        );
+        assert_eq!(split("file:///games/game.zip"), None);
+        assert_eq!(split("file:///games/game.swf"), None);
+    }
+}
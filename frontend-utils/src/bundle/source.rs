@@ -6,9 +6,9 @@ use std::io::{Error, Read};
 use std::path::{Path, PathBuf};
 
 pub mod directory;
-mod zip;
+pub(crate) mod zip;
 
-trait BundleSourceImpl {
+pub(crate) trait BundleSourceImpl {
     type Read: Read;
 
     /// Reads any file from the bundle.
@@ -21,7 +21,6 @@ struct TestResponse {
     body: Vec<u8>,
     chunk_gotten: bool,
     status: u16,
-    redirected: bool,
 }
 
 impl SuccessResponse for TestResponse {
@@ -47,8 +46,12 @@ impl SuccessResponse for TestResponse {
         self.status
     }
 
-    fn redirected(&self) -> bool {
-        self.redirected
+    fn requested_url(&self) -> Cow<str> {
+        Cow::Borrowed(&self.url)
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &[]
     }
 
     fn expected_length(&self) -> Result<Option<u64>, Error> {
@@ -119,7 +122,6 @@ impl NavigatorBackend for TestNavigatorBackend {
                     body: b"Hello, World!".to_vec(),
                     chunk_gotten: false,
                     status: 200,
-                    redirected: false,
                 });
 
                 Ok(response)
@@ -130,7 +132,7 @@ impl NavigatorBackend for TestNavigatorBackend {
             return Box::pin(async move {
                 Err(ErrorResponse {
                     url: request.url().to_string(),
-                    error: Error::HttpNotOk(request.url().to_string(), 0, false, 0),
+                    error: Error::HttpNotOk(request.url().to_string(), 0, false, vec![]),
                 })
             });
         }
@@ -233,7 +235,6 @@ impl NavigatorBackend for TestNavigatorBackend {
                 body,
                 chunk_gotten: false,
                 status: 0,
-                redirected: false,
             });
 
             Ok(response)
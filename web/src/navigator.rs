@@ -11,7 +11,7 @@ use ruffle_core::backend::navigator::{
 };
 use ruffle_core::config::NetworkingAccessMode;
 use ruffle_core::indexmap::IndexMap;
-use ruffle_core::loader::Error;
+use ruffle_core::loader::{Error, MAX_ERROR_BODY_SIZE};
 use ruffle_core::socket::{ConnectionState, SocketAction, SocketHandle};
 use std::borrow::Cow;
 use std::cell::RefCell;
@@ -298,7 +298,16 @@ impl NavigatorBackend for WebNavigatorBackend {
 
             for (header_name, header_val) in request.headers() {
                 headers
-                    .set(header_name, header_val)
+                    .append(header_name, header_val)
+                    .map_err(|_| ErrorResponse {
+                        url: url.to_string(),
+                        error: Error::FetchError("Got JS error".to_string()),
+                    })?;
+            }
+
+            if let Some(range) = request.range() {
+                headers
+                    .set("Range", &range.to_string())
                     .map_err(|_| ErrorResponse {
                         url: url.to_string(),
                         error: Error::FetchError("Got JS error".to_string()),
@@ -317,21 +326,31 @@ impl NavigatorBackend for WebNavigatorBackend {
                 url: url.to_string(),
                 error: Error::FetchError("Fetch result wasn't a WebResponse".to_string()),
             })?;
+            let requested_url = url.to_string();
             let url = response.url();
             let status = response.status();
             let redirected = response.redirected();
             if !response.ok() {
-                let error = Error::HttpNotOk(
-                    format!("HTTP status is not ok, got {}", response.status_text()),
-                    status,
-                    redirected,
-                    0,
-                );
+                let message = format!("HTTP status is not ok, got {}", response.status_text());
+                let mut body = Box::new(WebResponseWrapper {
+                    response,
+                    requested_url,
+                    headers: vec![],
+                    body_stream: None,
+                })
+                .body()
+                .await
+                .unwrap_or_default();
+                body.truncate(MAX_ERROR_BODY_SIZE);
+                let error = Error::HttpNotOk(message, status, redirected, body);
                 return Err(ErrorResponse { url, error });
             }
 
+            let headers = response_headers(&response);
             let wrapper: Box<dyn SuccessResponse> = Box::new(WebResponseWrapper {
                 response,
+                requested_url,
+                headers,
                 body_stream: None,
             });
 
@@ -428,11 +447,17 @@ impl NavigatorBackend for WebNavigatorBackend {
                     },
                     // Handle outgoing messages.
                     Either::Right((Ok(msg), _)) => {
-                        if let Err(e) = ws_write.send(Message::Bytes(msg)).await {
-                            tracing::warn!("Failed to send message to WebSocket {}", e);
-                            sender
-                                .try_send(SocketAction::Close(handle))
-                                .expect("working channel send");
+                        let len = msg.len();
+                        match ws_write.send(Message::Bytes(msg)).await {
+                            Ok(()) => sender
+                                .try_send(SocketAction::Written(handle, len))
+                                .expect("working channel send"),
+                            Err(e) => {
+                                tracing::warn!("Failed to send message to WebSocket {}", e);
+                                sender
+                                    .try_send(SocketAction::Close(handle))
+                                    .expect("working channel send");
+                            }
                         }
                     }
                     // The connection was closed.
@@ -450,8 +475,27 @@ impl NavigatorBackend for WebNavigatorBackend {
     }
 }
 
+/// Collect the headers of a response.
+///
+/// Browsers combine headers sent several times into one, separating their
+/// values by commas.
+fn response_headers(response: &WebResponse) -> Vec<(String, String)> {
+    let Ok(Some(entries)) = js_sys::try_iter(&response.headers()) else {
+        return vec![];
+    };
+
+    entries
+        .filter_map(|entry| {
+            let entry: Array = entry.ok()?.dyn_into().ok()?;
+            Some((entry.get(0).as_string()?, entry.get(1).as_string()?))
+        })
+        .collect()
+}
+
 struct WebResponseWrapper {
     response: WebResponse,
+    requested_url: String,
+    headers: Vec<(String, String)>,
     body_stream: Option<Rc<RefCell<ReadableStream>>>,
 }
 
@@ -485,10 +529,18 @@ impl SuccessResponse for WebResponseWrapper {
         self.response.status()
     }
 
+    fn requested_url(&self) -> Cow<str> {
+        Cow::Borrowed(&self.requested_url)
+    }
+
     fn redirected(&self) -> bool {
         self.response.redirected()
     }
 
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
     #[allow(clippy::await_holding_refcell_ref)]
     fn next_chunk(&mut self) -> OwnedFuture<Option<Vec<u8>>, Error> {
         if self.body_stream.is_none() {